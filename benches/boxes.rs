@@ -0,0 +1,58 @@
+mod common;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use raytracing::{shapes::{nd::HyperRectangle, Shape}, PreparedRay};
+
+use common::Rng;
+
+fn boxes(rng: &mut Rng, count: usize) -> Vec<HyperRectangle<f64, 3>>
+{
+    (0..count).map(|_| {
+            let c1 = rng.array(-8.0, 8.0);
+            let size = rng.array::<3>(0.1, 2.0);
+            HyperRectangle {
+                c1,
+                c2: core::array::from_fn(|k| c1[k] + size[k])
+            }
+        })
+        .collect()
+}
+
+/// Traces every ray against every box, once through [`Shape::raytrace`] and once through [`Shape::raytrace_prepared`], as a BVH traversal would.
+fn bench_boxes(c: &mut Criterion)
+{
+    let mut rng = Rng(0x2545f4914f6cdd1d);
+    let rays = rng.rays(256, 10.0);
+
+    let mut group = c.benchmark_group("boxes");
+    for count in [1, 16, 256]
+    {
+        let boxes = boxes(&mut rng, count);
+        group.throughput(Throughput::Elements((rays.len()*count) as u64));
+        group.bench_with_input(BenchmarkId::new("raytrace", count), &boxes, |b, boxes| b.iter(|| {
+            for ray in &rays
+            {
+                for shape in boxes
+                {
+                    black_box(shape.raytrace(black_box(ray)));
+                }
+            }
+        }));
+        group.bench_with_input(BenchmarkId::new("raytrace_prepared", count), &boxes, |b, boxes| b.iter(|| {
+            for ray in &rays
+            {
+                let ray = PreparedRay::new(*black_box(ray));
+                for shape in boxes
+                {
+                    black_box(shape.raytrace_prepared(&ray));
+                }
+            }
+        }));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_boxes);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,27 @@
+use raytracing::Ray;
+
+/// A small xorshift generator, so the scenes are the same on every run.
+pub struct Rng(pub u64);
+
+impl Rng
+{
+    pub fn next(&mut self, min: f64, max: f64) -> f64
+    {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        min + (max - min)*(self.0 >> 11) as f64/(1u64 << 53) as f64
+    }
+
+    pub fn array<const N: usize>(&mut self, min: f64, max: f64) -> [f64; N]
+    {
+        core::array::from_fn(|_| self.next(min, max))
+    }
+
+    /// Rays from random points in a cube of radius `radius` towards random points in the unit cube.
+    pub fn rays(&mut self, count: usize, radius: f64) -> Vec<Ray<f64, 3>>
+    {
+        (0..count).map(|_| Ray::new_from_to(self.array(-radius, radius), self.array(-1.0, 1.0)))
+            .collect()
+    }
+}
\ No newline at end of file
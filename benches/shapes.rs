@@ -0,0 +1,146 @@
+mod common;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, BenchmarkId, Criterion, Throughput};
+use raytracing::{
+    shapes::{
+        nd::{HyperCube, HyperPlane, HyperRectangle, HyperSphere},
+        Scene,
+        Shape,
+        Transform,
+        _3d::{Annulus, Disk, Polygon, Tetrahedron}
+    },
+    Ray
+};
+
+use common::Rng;
+
+const RAYS: usize = 1024;
+
+/// Measures both [`Shape::raytrace`] and [`Shape::raytrace_with_norm`] over the same rays. Throughput is reported in rays per second.
+fn bench_shape<S>(group: &mut BenchmarkGroup<WallTime>, name: &str, shape: &S, rays: &[Ray<f64, 3>])
+where
+    S: Shape<f64, 3> + ?Sized
+{
+    group.throughput(Throughput::Elements(rays.len() as u64));
+    group.bench_with_input(BenchmarkId::new("raytrace", name), rays, |b, rays| b.iter(|| {
+        for ray in rays
+        {
+            black_box(shape.raytrace(black_box(ray)));
+        }
+    }));
+    group.bench_with_input(BenchmarkId::new("raytrace_with_norm", name), rays, |b, rays| b.iter(|| {
+        for ray in rays
+        {
+            black_box(shape.raytrace_with_norm(black_box(ray)));
+        }
+    }));
+}
+
+fn sphere() -> HyperSphere<f64, 3>
+{
+    HyperSphere::new([0.0; 3], 1.0)
+}
+
+fn cube() -> HyperCube<f64, 3>
+{
+    HyperCube {center: [0.0; 3], radius: 0.8}
+}
+
+fn tetrahedron() -> Tetrahedron<f64>
+{
+    Tetrahedron {center: [0.0; 3], diameters: [2.0; 4]}
+}
+
+/// A mix of all kinds of shapes spread over a cube of radius `radius`.
+fn mixed(rng: &mut Rng, count: usize, radius: f64) -> Vec<Box<dyn Shape<f64, 3> + Send + Sync>>
+{
+    (0..count).map(|i| {
+            let r0 = rng.array(-radius, radius);
+            let size = rng.next(0.1, 1.0);
+            let shape: Box<dyn Shape<f64, 3> + Send + Sync> = match i % 5
+            {
+                0 => Box::new(HyperSphere::new(r0, size)),
+                1 => Box::new(HyperCube {center: r0, radius: size}),
+                2 => Box::new(Disk::new(r0, rng.array(-1.0, 1.0), size)),
+                3 => Box::new(Transform::new(Tetrahedron {center: [0.0; 3], diameters: [size*2.0; 4]})
+                    .rotate(rng.array(-1.0, 1.0), rng.next(0.0, 3.0))
+                    .translate(r0)),
+                _ => Box::new(Transform::new(HyperRectangle {c1: [-size; 3], c2: [size, size*0.5, size*0.25]})
+                    .rotate(rng.array(-1.0, 1.0), rng.next(0.0, 3.0))
+                    .translate(r0))
+            };
+            shape
+        })
+        .collect()
+}
+
+fn bench_primitives(c: &mut Criterion)
+{
+    let rays = Rng(0x5eed).rays(RAYS, 4.0);
+
+    let mut group = c.benchmark_group("nd");
+    bench_shape(&mut group, "HyperSphere", &sphere(), &rays);
+    bench_shape(&mut group, "HyperCube", &cube(), &rays);
+    bench_shape(&mut group, "HyperRectangle", &HyperRectangle {c1: [-1.0, -0.5, -0.25], c2: [1.0, 0.5, 0.25]}, &rays);
+    bench_shape(&mut group, "HyperPlane", &HyperPlane {r: [0.0; 3], n: [0.2, 1.0, -0.3]}, &rays);
+    group.finish();
+
+    let mut group = c.benchmark_group("3d");
+    bench_shape(&mut group, "Annulus", &Annulus::new([0.0; 3], [0.2, 1.0, -0.3], 0.5, 1.0), &rays);
+    bench_shape(&mut group, "Disk", &Disk::new([0.0; 3], [0.2, 1.0, -0.3], 1.0), &rays);
+    bench_shape(&mut group, "Polygon", &Polygon::new([[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [1.0, 1.0, 0.0], [-1.0, 1.0, 0.5], [-1.5, 0.0, 0.25]]), &rays);
+    bench_shape(&mut group, "Tetrahedron", &tetrahedron(), &rays);
+    group.finish();
+}
+
+fn bench_transforms(c: &mut Criterion)
+{
+    let rays = Rng(0x5eed).rays(RAYS, 4.0);
+
+    let mut group = c.benchmark_group("transform");
+    bench_shape(&mut group, "HyperSphere", &Transform::new(sphere()).scale([1.0, 0.5, 2.0]), &rays);
+    bench_shape(&mut group, "HyperCube", &Transform::new(cube()).rotate([1.0, 1.0, 0.0], 0.7), &rays);
+    bench_shape(&mut group, "Tetrahedron", &Transform::new(tetrahedron()).rotate([0.0, 1.0, 0.0], 0.3).translate([0.1, 0.0, 0.0]), &rays);
+    bench_shape(&mut group, "nested", &Transform::new(Transform::new(cube()).rotate([1.0, 0.0, 0.0], 0.5)).scale([1.0, 2.0, 1.0]), &rays);
+    group.finish();
+}
+
+fn bench_collections(c: &mut Criterion)
+{
+    let mut rng = Rng(0xc011);
+    let rays = rng.rays(RAYS, 4.0);
+
+    let mut group = c.benchmark_group("collection");
+    for count in [1, 16, 256]
+    {
+        let spheres: Vec<_> = (0..count).map(|_| HyperSphere::new(rng.array(-2.0, 2.0), rng.next(0.1, 0.5)))
+            .collect();
+        bench_shape(&mut group, &format!("Vec<HyperSphere>/{count}"), &spheres, &rays);
+        bench_shape(&mut group, &format!("Vec<Box<dyn Shape>>/{count}"), &mixed(&mut rng, count, 2.0), &rays);
+    }
+    group.finish();
+}
+
+fn bench_scenes(c: &mut Criterion)
+{
+    let mut rng = Rng(0x5ce7e);
+    let rays = rng.rays(RAYS, 8.0);
+
+    let mut group = c.benchmark_group("scene");
+    for count in [16, 256, 1024]
+    {
+        let mut scene = Scene::<f64, 3>::new();
+        scene.insert(Box::new(HyperPlane {r: [0.0, -4.0, 0.0], n: [0.0, 1.0, 0.0]}), ());
+        for shape in mixed(&mut rng, count - 1, 4.0)
+        {
+            scene.insert(shape, ());
+        }
+        bench_shape(&mut group, &count.to_string(), &scene, &rays);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_primitives, bench_transforms, bench_collections, bench_scenes);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,235 @@
+use alloc::vec::Vec;
+
+use num::Float;
+
+use crate::{Ray, Raytrace, RaytraceWithNorm};
+
+/// Rays stored as structure-of-arrays, with one array per coordinate of their origins and directions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RayBatch<F, const D: usize>
+where
+    F: Float
+{
+    pub r: [Vec<F>; D],
+    pub v: [Vec<F>; D]
+}
+
+impl<F, const D: usize> Default for RayBatch<F, D>
+where
+    F: Float
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl<F, const D: usize> FromIterator<Ray<F, D>> for RayBatch<F, D>
+where
+    F: Float
+{
+    fn from_iter<I>(rays: I) -> Self
+    where
+        I: IntoIterator<Item = Ray<F, D>>
+    {
+        let mut batch = Self::new();
+        for ray in rays
+        {
+            batch.push(ray);
+        }
+        batch
+    }
+}
+
+impl<F, const D: usize> RayBatch<F, D>
+where
+    F: Float
+{
+    pub fn new() -> Self
+    {
+        Self {
+            r: core::array::from_fn(|_| Vec::new()),
+            v: core::array::from_fn(|_| Vec::new())
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self
+    {
+        Self {
+            r: core::array::from_fn(|_| Vec::with_capacity(capacity)),
+            v: core::array::from_fn(|_| Vec::with_capacity(capacity))
+        }
+    }
+
+    pub fn push(&mut self, ray: Ray<F, D>)
+    {
+        for k in 0..D
+        {
+            self.r[k].push(ray.r[k]);
+            self.v[k].push(ray.v[k]);
+        }
+    }
+
+    /// The number of rays. All coordinate arrays must have this length.
+    pub fn len(&self) -> usize
+    {
+        self.r.first()
+            .map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    pub fn ray(&self, i: usize) -> Ray<F, D>
+    {
+        Ray {
+            r: self.r.each_ref().map(|r| r[i]),
+            v: self.v.each_ref().map(|v| v[i])
+        }
+    }
+
+    pub fn rays(&self) -> impl ExactSizeIterator<Item = Ray<F, D>> + '_
+    {
+        (0..self.len()).map(|i| self.ray(i))
+    }
+}
+
+/// The results of tracing a [`RayBatch`], with the `t` of each ray and its normal stored as one array per coordinate. Misses have a zero normal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaytraceBatch<F, const D: usize>
+where
+    F: Float
+{
+    pub t: Vec<F>,
+    pub n: [Vec<F>; D]
+}
+
+impl<F, const D: usize> RaytraceBatch<F, D>
+where
+    F: Float
+{
+    pub fn miss(len: usize) -> Self
+    {
+        Self {
+            t: alloc::vec![F::infinity(); len],
+            n: core::array::from_fn(|_| alloc::vec![F::zero(); len])
+        }
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.t.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.t.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> RaytraceWithNorm<F, D>
+    {
+        let raytrace = Raytrace {
+            t: self.t[i]
+        };
+        RaytraceWithNorm {
+            raytrace,
+            n: raytrace.is_hit().then(|| self.n.each_ref().map(|n| n[i])),
+            ..RaytraceWithNorm::miss()
+        }
+    }
+
+    pub fn set(&mut self, i: usize, raytrace: &RaytraceWithNorm<F, D>)
+    {
+        self.t[i] = raytrace.t;
+        let n = raytrace.n.unwrap_or([F::zero(); D]);
+        for (nk, n) in self.n.iter_mut()
+            .zip(n)
+        {
+            nk[i] = n;
+        }
+    }
+
+    /// Keeps the nearer hit of each ray, preferring `self` on ties, like [`RaytraceWithNorm::min`].
+    pub fn min(mut self, rhs: Self) -> Self
+    {
+        for i in 0..self.len()
+        {
+            if self.t[i] > rhs.t[i]
+            {
+                self.t[i] = rhs.t[i];
+                for k in 0..D
+                {
+                    self.n[k][i] = rhs.n[k][i];
+                }
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::{shapes::{nd::{HyperCube, HyperPlane, HyperRectangle, HyperSphere}, Scene, Shape, Transform, _3d::Tetrahedron}, tests::Rng, Ray};
+
+    use super::RayBatch;
+
+    fn check<S>(shape: &S, rng: &mut Rng) -> usize
+    where
+        S: Shape<f64, 3> + ?Sized
+    {
+        let rays: RayBatch<f64, 3> = (0..500).map(|i| {
+                let r = rng.array(-4.0, 4.0);
+                let to = rng.array::<3>(-1.0, 1.0);
+                Ray {
+                    r,
+                    v: if i % 9 == 0 {[0.0, 0.0, 1.0]} else {core::array::from_fn(|k| to[k] - r[k])}
+                }
+            })
+            .collect();
+        let batch = shape.raytrace_batch(&rays);
+        assert_eq!(batch.len(), rays.len());
+
+        let mut hits = 0;
+        for (i, ray) in rays.rays().enumerate()
+        {
+            let expected = shape.raytrace_with_norm(&ray);
+            let raytrace = batch.get(i);
+            assert_eq!(raytrace.t.to_bits(), expected.t.to_bits(), "{ray:?}");
+            assert_eq!(raytrace.n, expected.n, "{ray:?}");
+            hits += expected.is_hit() as usize;
+        }
+        hits
+    }
+
+    #[test]
+    fn test_batch()
+    {
+        let mut rng = Rng::new(47);
+        let sphere = HyperSphere::new([0.5, -0.25, 0.0], 1.5);
+        let rectangle = HyperRectangle {c1: [-1.0, -0.5, -2.0], c2: [1.5, 0.5, 1.0]};
+        let cube = HyperCube {center: [0.25, 0.0, -0.5], radius: 1.0};
+        let plane = HyperPlane {r: [0.0, -0.5, 0.0], n: [0.2, 1.0, -0.3]};
+        let tetrahedron = Tetrahedron {center: [0.0, 0.0, 0.0], diameters: [3.0; 4]};
+
+        assert!(check(&sphere, &mut rng) > 100);
+        assert!(check(&rectangle, &mut rng) > 100);
+        assert!(check(&cube, &mut rng) > 100);
+        assert!(check(&plane, &mut rng) > 100);
+        assert!(check(&tetrahedron, &mut rng) > 100);
+        assert!(check(&Transform::new(cube).rotate([1.0, 1.0, 0.0], 0.7).scale([1.0, 2.0, 0.5]), &mut rng) > 100);
+        assert!(check(&[sphere, HyperSphere::new([1.0, 1.0, 1.0], 1.0)], &mut rng) > 100);
+
+        let mut scene = Scene::<f64, 3>::new();
+        scene.insert(Box::new(sphere), ());
+        scene.insert(Box::new(Transform::new(rectangle).translate([0.5, 0.0, 0.0])), ());
+        scene.insert(Box::new(plane), ());
+        assert!(check(&scene, &mut rng) > 100);
+        assert!(check(&scene as &dyn Shape<f64, 3>, &mut rng) > 100);
+
+        assert!(check(&Vec::<HyperSphere<f64, 3>>::new(), &mut rng) == 0);
+        assert!(RayBatch::<f64, 3>::new().is_empty());
+    }
+}
\ No newline at end of file
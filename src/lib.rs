@@ -1,20 +1,29 @@
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 #![feature(more_float_constants)]
 #![feature(let_chains)]
 #![feature(const_trait_impl)]
 #![feature(iter_next_chunk)]
-#![feature(slice_as_array)]
 #![feature(specialization)]
+#![feature(portable_simd)]
 #![allow(clippy::excessive_precision)]
 
+extern crate alloc;
+
 moddef::moddef!(
     pub mod {
         shapes,
         matrix,
+        output for cfg(feature = "std"),
+        render,
+        texture,
         vec2,
-        vec3
+        vec3,
+        visibility
     },
     flat(pub) mod {
+        batch,
+        packet,
+        quaternion,
         ray,
         raytrace
     }
@@ -23,72 +32,28 @@ moddef::moddef!(
 #[cfg(test)]
 mod tests
 {
-    use core::f64::consts::{FRAC_1_SQRT_2, FRAC_1_SQRT_3, TAU};
-    use std::time::SystemTime;
+    use core::f64::consts::{FRAC_1_SQRT_2, FRAC_1_SQRT_3};
+    use core::sync::atomic::AtomicBool;
 
     use image::Rgb;
 
-    use self::shapes::{Shape, Transform};
+    use self::{render::{Dither, Framebuffer, Lens, ProgressiveRenderer, Renderer, ToneMapper, ToneMapping}, shapes::Shape};
 
     use super::*;
 
-    pub fn project_3d_spin<S>(shape: &S, lens_pos: [f64; 3], lens_size: f64, lens_bend: f64)
-    where
-        S: Shape<f64, 3> + Clone
-    {
-        const SPIN_SPEED: f64 = TAU * 0.02;
-
-        let t0 = SystemTime::now();
-        loop
-        {
-            let t = SystemTime::now();
-            let dt = t.duration_since(t0).unwrap().as_secs_f64();
-
-            let shape = Transform::new(shape.clone()).rotate([0.0, 1.0, 0.0], SPIN_SPEED * dt);
+    /// Side length of the golden images.
+    const GOLDEN_SIZE: usize = 128;
+    /// CIE76 color difference below which two pixels look the same.
+    const GOLDEN_DELTA_E: f64 = 2.3;
+    /// Fraction of pixels that may differ from the reference, leaving room for rounding differences between platforms along edges.
+    const GOLDEN_TOLERANCE: f64 = 0.002;
 
-            project_3d_once(&shape, lens_pos, lens_size, lens_bend);
-        }
-    }
-
-    pub fn project_3d_once<S>(shape: &S, lens_pos: [f64; 3], lens_size: f64, lens_bend: f64)
+    /// Renders `shape` through a fixed lens, shading it by its normals and darkening it with distance.
+    pub fn render_3d<S>(shape: &S, lens_pos: [f64; 3], lens_size: f64, lens_bend: f64, size: usize, passes: usize) -> Framebuffer<[u8; 3]>
     where
         S: Shape<f64, 3>
     {
-        const N: usize = 256;
-
-        let [lens_x, lens_y, lens_z] = lens_pos;
-
-        let t: Box<[[_; N]; N]> = (0..N)
-            .map(|y| {
-                core::array::from_fn(|x| {
-                    let x = x as f64 / (N - 1) as f64 * 2.0 - 1.0;
-                    let y = 1.0 - y as f64 / (N - 1) as f64 * 2.0;
-
-                    let ray = Ray {
-                        r: [x * lens_size + lens_x, y * lens_size + lens_y, lens_z],
-                        v: vec3::normalize([x * lens_bend, y * lens_bend, 1.0])
-                    };
-
-                    shape.raytrace_with_norm(&ray)
-                })
-            })
-            .collect::<Vec<_>>()
-            .into_boxed_slice()
-            .into_array::<N>()
-            .unwrap();
-
-        /*let t_min = t.iter()
-            .flat_map(|t| t.iter().map(|raytrace| raytrace.t))
-            .filter(|t| t.is_finite())
-            .reduce(f64::min)
-            .unwrap_or(0.0);
-        let t_max = t.iter()
-            .flat_map(|t| t.iter().map(|raytrace| raytrace.t))
-            .filter(|t| t.is_finite())
-            .reduce(f64::max)
-            .unwrap_or(1.0);*/
-
-        const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+        const BACKGROUND: [f64; 3] = [1.0, 1.0, 1.0];
 
         const DIR_RED: [f64; 3] = [-FRAC_1_SQRT_3, -FRAC_1_SQRT_3, -FRAC_1_SQRT_3];
         const DIR_GREEN: [f64; 3] = [-FRAC_1_SQRT_3, -FRAC_1_SQRT_3, FRAC_1_SQRT_3];
@@ -96,8 +61,8 @@ mod tests
 
         const BRIGHTNESS: f64 = 100.0;
 
-        image::RgbImage::from_fn(N as u32, N as u32, move |x, y| {
-            let raytrace = t[y as usize][x as usize];
+        let renderer = Renderer::new(Lens::new(lens_pos, lens_size, lens_bend), size, size);
+        let mut progressive = ProgressiveRenderer::new(renderer, shape, |_, raytrace| {
             if raytrace.t.is_finite()
                 && let Some(n) = raytrace.n
             {
@@ -107,15 +72,104 @@ mod tests
                 let g = l * (vec3::mul_dot(DIR_GREEN, n) * 0.5 + 0.5);
                 let b = l * (vec3::mul_dot(DIR_BLUE, n) * 0.5 + 0.5);
 
-                Rgb([(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8])
+                [r, g, b]
             }
             else
             {
                 BACKGROUND
             }
-        })
-        .save("output.png")
-        .unwrap();
+        });
+        progressive.run(passes, &AtomicBool::new(false), |_| ());
+
+        ToneMapper::new(ToneMapping::Aces)
+            .with_exposure(1.0)
+            .with_dither(Dither::Ordered)
+            .apply(&progressive.estimate())
+    }
+
+    /// CIELAB coordinates of an sRGB color, relative to the D65 white point.
+    fn lab([r, g, b]: [u8; 3]) -> [f64; 3]
+    {
+        let [r, g, b] = [r, g, b].map(|c| render::srgb_decode(c as f64/255.0));
+        let xyz = [
+            (0.4124564*r + 0.3575761*g + 0.1804375*b)/0.95047,
+            0.2126729*r + 0.7151522*g + 0.0721750*b,
+            (0.0193339*r + 0.1191920*g + 0.9503041*b)/1.08883
+        ];
+        let [fx, fy, fz] = xyz.map(|t| if t > 216.0/24389.0 {t.cbrt()} else {(24389.0/27.0*t + 16.0)/116.0});
+        [116.0*fy - 16.0, 500.0*(fx - fy), 200.0*(fy - fz)]
+    }
+
+    /// Renders `shape` and compares it to the reference image `tests/golden/{name}.png`.
+    ///
+    /// Pixels count as different when their CIE76 difference exceeds [`GOLDEN_DELTA_E`], and the test fails if more than [`GOLDEN_TOLERANCE`] of them do.
+    /// Set `UPDATE_GOLDEN` to write the render as the new reference instead.
+    pub fn assert_golden<S>(name: &str, shape: &S, lens_pos: [f64; 3], lens_size: f64, lens_bend: f64)
+    where
+        S: Shape<f64, 3>
+    {
+        const PASSES: usize = 4;
+
+        let image = render_3d(shape, lens_pos, lens_size, lens_bend, GOLDEN_SIZE, PASSES);
+        let image = image::RgbImage::from_fn(GOLDEN_SIZE as u32, GOLDEN_SIZE as u32, |x, y| Rgb(*image.get(x as usize, y as usize).unwrap()));
+
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("tests/golden/{name}.png"));
+        if std::env::var_os("UPDATE_GOLDEN").is_some()
+        {
+            image.save(&path).unwrap();
+            return
+        }
+        let reference = image::open(&path)
+            .unwrap_or_else(|error| panic!("missing reference {}, run with UPDATE_GOLDEN=1 to create it: {error}", path.display()))
+            .to_rgb8();
+        assert_eq!(reference.dimensions(), image.dimensions(), "{name}");
+
+        let different = image.pixels()
+            .zip(reference.pixels())
+            .filter(|(a, b)| {
+                let (a, b) = (lab(a.0), lab(b.0));
+                a.into_iter()
+                    .zip(b)
+                    .map(|(a, b)| (a - b)*(a - b))
+                    .sum::<f64>()
+                    .sqrt() > GOLDEN_DELTA_E
+            })
+            .count();
+        if different as f64 > GOLDEN_TOLERANCE*(GOLDEN_SIZE*GOLDEN_SIZE) as f64
+        {
+            let actual = std::env::temp_dir().join(format!("{name}.png"));
+            image.save(&actual).unwrap();
+            panic!("{different} pixels of {name} differ from {}, the render was saved to {}", path.display(), actual.display());
+        }
+    }
+
+    /// Small deterministic xorshift generator, so that randomized tests are reproducible
+    pub struct Rng(u64);
+
+    impl Rng
+    {
+        pub fn new(seed: u64) -> Self
+        {
+            Self(seed.max(1))
+        }
+
+        pub fn next_f64(&mut self) -> f64
+        {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 11) as f64/(1u64 << 53) as f64
+        }
+
+        pub fn range(&mut self, min: f64, max: f64) -> f64
+        {
+            min + (max - min)*self.next_f64()
+        }
+
+        pub fn array<const N: usize>(&mut self, min: f64, max: f64) -> [f64; N]
+        {
+            core::array::from_fn(|_| self.range(min, max))
+        }
     }
 
     #[test]
@@ -1,7 +1,19 @@
-use core::iter::Sum;
+use core::{error::Error, fmt::Display, iter::Sum};
 
 use num::Float;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SingularMatrixError;
+
+impl Display for SingularMatrixError
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result
+    {
+        write!(f, "matrix is singular and cannot be inverted")
+    }
+}
+impl Error for SingularMatrixError {}
+
 mod private
 {
     use num::{traits::{ConstOne, ConstZero}, Float};
@@ -94,4 +106,171 @@ where
             ).next_chunk()
             .unwrap_unchecked()
     }
+}
+
+pub fn transpose<F, const M: usize, const N: usize>(matrix: [[F; N]; M]) -> [[F; M]; N]
+where
+    F: Float
+{
+    core::array::from_fn(|i| core::array::from_fn(|j| matrix[j][i]))
+}
+
+struct Lu<F, const N: usize>
+{
+    lu: [[F; N]; N],
+    p: [usize; N],
+    odd: bool,
+    singular: bool
+}
+
+/// LU decomposition with partial pivoting, where `L` has a unit diagonal and both factors are stored in the same matrix.
+///
+/// Columns without a nonzero pivot are skipped. The matrix counts as singular if a pivot is within rounding error of zero, relative to the largest
+/// entry of the row it came from, so that badly scaled but invertible matrices are still accepted.
+fn lu<F, const N: usize>(matrix: [[F; N]; N]) -> Lu<F, N>
+where
+    F: Float
+{
+    let scale = matrix.map(|row| row.into_iter().fold(F::zero(), |max, m| max.max(m.abs())));
+    let n_eps = F::from(N).unwrap()*F::epsilon();
+
+    let mut lu = matrix;
+    let mut p: [usize; N] = core::array::from_fn(|i| i);
+    let mut odd = false;
+    let mut singular = false;
+
+    for k in 0..N
+    {
+        let pivot = (k..N).reduce(|a, b| if lu[b][k].abs() > lu[a][k].abs() {b} else {a})
+            .unwrap_or(k);
+        if pivot != k
+        {
+            lu.swap(pivot, k);
+            p.swap(pivot, k);
+            odd = !odd;
+        }
+        singular |= lu[k][k].abs() <= n_eps*scale[p[k]] || lu[k][k].is_nan();
+        if lu[k][k] == F::zero()
+        {
+            continue
+        }
+
+        let (top, bottom) = lu.split_at_mut(k + 1);
+        let row_k = &top[k];
+        for row in bottom
+        {
+            let l = row[k]/row_k[k];
+            row[k] = l;
+            for (r, &rk) in row[k + 1..].iter_mut()
+                .zip(&row_k[k + 1..])
+            {
+                *r = *r - l*rk
+            }
+        }
+    }
+
+    Lu {
+        lu,
+        p,
+        odd,
+        singular
+    }
+}
+
+pub fn determinant<F, const N: usize>(matrix: [[F; N]; N]) -> F
+where
+    F: Float
+{
+    let Lu {lu, odd, ..} = lu(matrix);
+    let det = (0..N).fold(F::one(), |det, i| det*lu[i][i]);
+    if odd {-det} else {det}
+}
+
+pub fn inverse<F, const N: usize>(matrix: [[F; N]; N]) -> Result<[[F; N]; N], SingularMatrixError>
+where
+    F: Float
+{
+    let Lu {lu, p, singular, ..} = lu(matrix);
+    if singular || (0..N).any(|i| !lu[i][i].is_finite())
+    {
+        return Err(SingularMatrixError)
+    }
+
+    let mut inv = [[F::zero(); N]; N];
+    for c in 0..N
+    {
+        let mut x: [F; N] = core::array::from_fn(|i| if p[i] == c {F::one()} else {F::zero()});
+        for i in 0..N
+        {
+            for j in 0..i
+            {
+                x[i] = x[i] - lu[i][j]*x[j]
+            }
+        }
+        for i in (0..N).rev()
+        {
+            for j in i + 1..N
+            {
+                x[i] = x[i] - lu[i][j]*x[j]
+            }
+            x[i] = x[i]/lu[i][i]
+        }
+        for (row, x) in inv.iter_mut()
+            .zip(x)
+        {
+            row[c] = x
+        }
+    }
+    Ok(inv)
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::SingularMatrixError;
+
+    #[test]
+    fn test()
+    {
+        let m = [
+            [0.0, 2.0, 1.0],
+            [1.0, -1.0, 3.0],
+            [4.0, 0.5, -2.0]
+        ];
+
+        assert!((super::determinant::<f64, 3>(m) - 32.5).abs() < 1e-12);
+        assert_eq!(super::transpose(super::transpose(m)), m);
+
+        let i = super::mul_matrix_matrix(&m, &super::inverse(m).unwrap());
+        for (i, e) in i.as_flattened().iter().zip(super::identity::<f64, 3>().as_flattened())
+        {
+            assert!((i - e).abs() < 1e-12);
+        }
+
+        let singular = [
+            [1.0, 2.0],
+            [2.0, 4.0]
+        ];
+        assert_eq!(super::inverse(singular), Err(SingularMatrixError));
+        assert_eq!(super::determinant(singular), 0.0);
+
+        // Badly scaled, but far from singular
+        let scale = [
+            [1e9, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1e-8]
+        ];
+        assert_eq!(super::inverse(scale), Ok([[1e-9, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1e8]]));
+        assert_eq!(super::determinant(scale), 1e9*1e-8);
+        assert_eq!(super::inverse([[f64::NAN, 0.0], [0.0, 1.0]]), Err(SingularMatrixError));
+
+        // Rank 2, but rounding leaves a tiny nonzero last pivot
+        let rank_2 = [
+            [1.0, 2.0, 3.0],
+            [4.0, 5.0, 6.0],
+            [7.0, 8.0, 9.0]
+        ];
+        assert_eq!(super::inverse(rank_2), Err(SingularMatrixError));
+        assert!(super::determinant::<f64, 3>(rank_2).abs() < 1e-12);
+    }
 }
\ No newline at end of file
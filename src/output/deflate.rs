@@ -0,0 +1,245 @@
+//! Minimal zlib streams, either stored or compressed with the fixed deflate Huffman codes.
+
+const STORED_BLOCK_MAX: usize = 0xffff;
+
+const WINDOW: usize = 1 << 15;
+const HASH_BITS: u32 = 15;
+const MAX_CHAIN: usize = 64;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13
+];
+
+/// Writes bits least significant first, as deflate requires.
+struct BitWriter
+{
+    out: Vec<u8>,
+    buffer: u64,
+    len: u32
+}
+
+impl BitWriter
+{
+    fn bits(&mut self, value: u32, len: u32)
+    {
+        self.buffer |= (value as u64) << self.len;
+        self.len += len;
+        while self.len >= 8
+        {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// Huffman codes are defined most significant bit first.
+    fn code(&mut self, code: u32, len: u32)
+    {
+        self.bits(code.reverse_bits() >> (32 - len), len)
+    }
+
+    fn symbol(&mut self, symbol: u16)
+    {
+        match symbol
+        {
+            0..=143 => self.code(0x30 + symbol as u32, 8),
+            144..=255 => self.code(0x190 + (symbol - 144) as u32, 9),
+            256..=279 => self.code((symbol - 256) as u32, 7),
+            _ => self.code(0xc0 + (symbol - 280) as u32, 8)
+        }
+    }
+
+    fn length(&mut self, length: usize)
+    {
+        let i = LENGTH_BASE.partition_point(|&base| base as usize <= length) - 1;
+        self.symbol(257 + i as u16);
+        self.bits((length - LENGTH_BASE[i] as usize) as u32, LENGTH_EXTRA[i] as u32);
+    }
+
+    fn distance(&mut self, distance: usize)
+    {
+        let i = DISTANCE_BASE.partition_point(|&base| base as usize <= distance) - 1;
+        self.code(i as u32, 5);
+        self.bits((distance - DISTANCE_BASE[i] as usize) as u32, DISTANCE_EXTRA[i] as u32);
+    }
+
+    fn finish(mut self) -> Vec<u8>
+    {
+        if self.len > 0
+        {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+fn hash(data: &[u8]) -> usize
+{
+    let x = u32::from_le_bytes([data[0], data[1], data[2], 0]);
+    (x.wrapping_mul(0x9e3779b1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Wraps `data` in a zlib stream made of stored (uncompressed) deflate blocks.
+pub(super) fn zlib_stored(data: &[u8]) -> Vec<u8>
+{
+    let blocks = data.len().div_ceil(STORED_BLOCK_MAX).max(1);
+    let mut out = Vec::with_capacity(2 + blocks*5 + data.len() + 4);
+    out.extend([0x78, 0x01]);
+
+    let mut chunks = data.chunks(STORED_BLOCK_MAX).peekable();
+    if chunks.peek().is_none()
+    {
+        out.extend([1, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next()
+    {
+        let len = chunk.len() as u16;
+        out.push(chunks.peek().is_none() as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(chunk);
+    }
+
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+/// Compresses `data` into a zlib stream of a single fixed-Huffman deflate block, using a hash-chained LZ77 match search.
+pub(super) fn zlib_compress(data: &[u8]) -> Vec<u8>
+{
+    let mut w = BitWriter {
+        out: vec![0x78, 0x01],
+        buffer: 0,
+        len: 0
+    };
+    w.bits(1, 1);
+    w.bits(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let insert = |head: &mut [usize], prev: &mut [usize], i: usize| {
+        if i + MIN_MATCH <= data.len()
+        {
+            let h = hash(&data[i..]);
+            prev[i % WINDOW] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len()
+    {
+        let mut best = (0, 0);
+        if i + MIN_MATCH <= data.len()
+        {
+            let max = (data.len() - i).min(MAX_MATCH);
+            let mut candidate = head[hash(&data[i..])];
+            let mut chain = 0;
+            while candidate != usize::MAX && i - candidate <= WINDOW && chain < MAX_CHAIN
+            {
+                let length = data[candidate..].iter()
+                    .zip(&data[i..i + max])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if length > best.0
+                {
+                    best = (length, i - candidate);
+                    if length == max
+                    {
+                        break
+                    }
+                }
+                let next = prev[candidate % WINDOW];
+                if next >= candidate
+                {
+                    break
+                }
+                candidate = next;
+                chain += 1;
+            }
+        }
+
+        let (length, distance) = best;
+        if length >= MIN_MATCH
+        {
+            w.length(length);
+            w.distance(distance);
+            for j in i..i + length
+            {
+                insert(&mut head, &mut prev, j);
+            }
+            i += length;
+        }
+        else
+        {
+            w.symbol(data[i] as u16);
+            insert(&mut head, &mut prev, i);
+            i += 1;
+        }
+    }
+    w.symbol(256);
+
+    let mut out = w.finish();
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+pub(super) fn adler32(data: &[u8]) -> u32
+{
+    const MOD: u32 = 65521;
+
+    let (a, b) = data.chunks(5552)
+        .fold((1u32, 0u32), |(mut a, mut b), chunk| {
+            for &x in chunk
+            {
+                a += x as u32;
+                b += a;
+            }
+            (a % MOD, b % MOD)
+        });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod test
+{
+    #[test]
+    fn test_adler32()
+    {
+        assert_eq!(super::adler32(b"Wikipedia"), 0x11e60398);
+        assert_eq!(super::adler32(&[0xff; 100000]), {
+            let (a, b) = (0..100000u64).fold((1u64, 0u64), |(a, b), _| (a + 0xff, b + a + 0xff));
+            (((b % 65521) << 16) | (a % 65521)) as u32
+        });
+    }
+
+    #[test]
+    fn test_zlib()
+    {
+        let mut rng = crate::tests::Rng::new(42);
+        let data: Vec<u8> = (0..200000)
+            .map(|i| if i % 1000 < 500 {(i % 7) as u8} else {(rng.next_f64()*4.0) as u8})
+            .collect();
+
+        for data in [&data[..], &[], b"a", b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"]
+        {
+            let stored = super::zlib_stored(data);
+            let compressed = super::zlib_compress(data);
+            assert_eq!(miniz_oxide::inflate::decompress_to_vec_zlib(&stored).unwrap(), data);
+            assert_eq!(miniz_oxide::inflate::decompress_to_vec_zlib(&compressed).unwrap(), data);
+        }
+        assert!(super::zlib_compress(&data).len() < data.len()/2);
+    }
+}
\ No newline at end of file
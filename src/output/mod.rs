@@ -0,0 +1,96 @@
+use std::io;
+
+moddef::moddef!(
+    flat(pub) mod {
+        open_exr,
+        pfm,
+        png,
+        ppm
+    },
+    mod {
+        deflate
+    }
+);
+
+/// A single colour channel. Values are mapped to `[0, 1]` for 8-bit formats.
+pub trait Channel: Copy
+{
+    fn to_f32(self) -> f32;
+
+    fn to_u8(self) -> u8
+    {
+        (self.to_f32().clamp(0.0, 1.0)*255.0).round() as u8
+    }
+}
+
+impl Channel for f32
+{
+    fn to_f32(self) -> f32
+    {
+        self
+    }
+}
+
+impl Channel for f64
+{
+    fn to_f32(self) -> f32
+    {
+        self as f32
+    }
+}
+
+impl Channel for u8
+{
+    fn to_f32(self) -> f32
+    {
+        self as f32/255.0
+    }
+
+    fn to_u8(self) -> u8
+    {
+        self
+    }
+}
+
+/// A pixel made of one or more channels, such as a greyscale value or an `[r, g, b]` array.
+pub trait Pixel
+{
+    type Channel: Channel;
+
+    const CHANNELS: usize;
+
+    fn channels(&self) -> &[Self::Channel];
+}
+
+impl<C> Pixel for C
+where
+    C: Channel
+{
+    type Channel = C;
+
+    const CHANNELS: usize = 1;
+
+    fn channels(&self) -> &[Self::Channel]
+    {
+        core::slice::from_ref(self)
+    }
+}
+
+impl<C, const N: usize> Pixel for [C; N]
+where
+    C: Channel
+{
+    type Channel = C;
+
+    const CHANNELS: usize = N;
+
+    fn channels(&self) -> &[Self::Channel]
+    {
+        self
+    }
+}
+
+fn unsupported_channels(format: &str, channels: usize) -> io::Error
+{
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{format} does not support {channels} channels"))
+}
\ No newline at end of file
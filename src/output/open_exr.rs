@@ -0,0 +1,479 @@
+use std::io::{self, Write};
+
+use num::Float;
+
+use crate::render::{Framebuffer, Render};
+
+use super::{deflate, Channel, Pixel};
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+const VERSION: u32 = 2;
+const LONG_NAMES: u32 = 0x400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExrCompression
+{
+    #[default]
+    None,
+    /// Deflate over blocks of 16 scanlines.
+    Zip
+}
+
+impl ExrCompression
+{
+    fn id(self) -> u8
+    {
+        match self
+        {
+            ExrCompression::None => 0,
+            ExrCompression::Zip => 3
+        }
+    }
+
+    fn scanlines(self) -> usize
+    {
+        match self
+        {
+            ExrCompression::None => 1,
+            ExrCompression::Zip => 16
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExrSampleType
+{
+    #[default]
+    Half,
+    Float
+}
+
+impl ExrSampleType
+{
+    fn id(self) -> u32
+    {
+        match self
+        {
+            ExrSampleType::Half => 1,
+            ExrSampleType::Float => 2
+        }
+    }
+}
+
+/// A group of channels from one framebuffer, named `layer.channel` in the file. Channels of a layer with an empty name are written at the top level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExrLayer
+{
+    name: String,
+    channels: Vec<String>,
+    sample_type: ExrSampleType,
+    width: usize,
+    height: usize,
+    samples: Vec<f32>
+}
+
+impl ExrLayer
+{
+    /// Channels default to `Y`, `YA`, `RGB` or `RGBA` depending on the pixel type, and are stored as half floats.
+    pub fn new<P>(name: impl Into<String>, framebuffer: &Framebuffer<P>) -> Self
+    where
+        P: Pixel
+    {
+        let channels = match P::CHANNELS
+        {
+            1 => vec!["Y".into()],
+            2 => vec!["Y".into(), "A".into()],
+            3 => vec!["R".into(), "G".into(), "B".into()],
+            4 => vec!["R".into(), "G".into(), "B".into(), "A".into()],
+            n => (0..n).map(|i| i.to_string()).collect()
+        };
+        Self {
+            name: name.into(),
+            channels,
+            sample_type: ExrSampleType::default(),
+            width: framebuffer.width(),
+            height: framebuffer.height(),
+            samples: framebuffer.pixels()
+                .iter()
+                .flat_map(|p| p.channels().iter().map(|c| c.to_f32()))
+                .collect()
+        }
+    }
+
+    pub fn with_channels<S>(self, channels: impl IntoIterator<Item = S>) -> Self
+    where
+        S: Into<String>
+    {
+        let channels: Vec<String> = channels.into_iter()
+            .map(Into::into)
+            .collect();
+        assert_eq!(channels.len(), self.channels.len(), "layer must have one name per channel");
+        Self {
+            channels,
+            ..self
+        }
+    }
+
+    pub fn with_sample_type(self, sample_type: ExrSampleType) -> Self
+    {
+        Self {
+            sample_type,
+            ..self
+        }
+    }
+
+    fn channel_name(&self, c: usize) -> String
+    {
+        if self.name.is_empty()
+        {
+            self.channels[c].clone()
+        }
+        else
+        {
+            format!("{}.{}", self.name, self.channels[c])
+        }
+    }
+}
+
+/// A single-part scanline OpenEXR image made of one or more layers of the same size.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Exr
+{
+    pub compression: ExrCompression,
+    pub layers: Vec<ExrLayer>
+}
+
+impl Exr
+{
+    pub fn new(compression: ExrCompression) -> Self
+    {
+        Self {
+            compression,
+            layers: vec![]
+        }
+    }
+
+    pub fn with_layer(mut self, layer: ExrLayer) -> Self
+    {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Puts the beauty pass at the top level as half floats, and every rendered output variable in its own full-precision layer.
+    pub fn from_render<F>(render: &Render<F>, compression: ExrCompression) -> Self
+    where
+        F: Float + Channel
+    {
+        let aovs = &render.aovs;
+        let layers = [
+            Some(ExrLayer::new("", &render.beauty)),
+            aovs.depth.as_ref().map(|depth| ExrLayer::new("depth", depth).with_channels(["Z"])),
+            aovs.normal.as_ref().map(|normal| ExrLayer::new("normal", normal).with_channels(["X", "Y", "Z"])),
+            aovs.position.as_ref().map(|position| ExrLayer::new("position", position).with_channels(["X", "Y", "Z"])),
+            aovs.object_id.as_ref().map(|object_id| ExrLayer::new("object_id", object_id).with_channels(["id"])),
+            aovs.uv.as_ref().map(|uv| ExrLayer::new("uv", uv).with_channels(["U", "V"]))
+        ];
+        Self {
+            compression,
+            layers: layers.into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(i, layer)| if i == 0 {layer} else {layer.with_sample_type(ExrSampleType::Float)})
+                .collect()
+        }
+    }
+}
+
+pub fn write_exr<W>(mut w: W, exr: &Exr) -> io::Result<()>
+where
+    W: Write
+{
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message.to_string());
+
+    let Some(first) = exr.layers.first()
+    else
+    {
+        return Err(invalid("EXR image must have at least one layer"))
+    };
+    let (width, height) = (first.width, first.height);
+    if exr.layers.iter().any(|layer| (layer.width, layer.height) != (width, height))
+    {
+        return Err(invalid("EXR layers must all have the same size"))
+    }
+    let (Ok(x_max @ 0..), Ok(y_max @ 0..)) = (i32::try_from(width as i64 - 1), i32::try_from(height as i64 - 1))
+    else
+    {
+        return Err(invalid("EXR dimensions must be between 1 and 2^31"))
+    };
+
+    let mut channels: Vec<(String, &ExrLayer, usize)> = exr.layers.iter()
+        .flat_map(|layer| (0..layer.channels.len()).map(move |c| (layer.channel_name(c), layer, c)))
+        .collect();
+    channels.sort_by(|a, b| a.0.cmp(&b.0));
+    if channels.windows(2).any(|pair| pair[0].0 == pair[1].0)
+    {
+        return Err(invalid("EXR channel names must be unique"))
+    }
+    if channels.iter().any(|(name, ..)| name.is_empty() || name.len() > 255 || name.contains('\0'))
+    {
+        return Err(invalid("EXR channel names must be between 1 and 255 bytes, without null characters"))
+    }
+    let version = if channels.iter().any(|(name, ..)| name.len() > 31) {VERSION | LONG_NAMES} else {VERSION};
+
+    let mut chlist = vec![];
+    for (name, layer, _) in &channels
+    {
+        chlist.extend(name.as_bytes());
+        chlist.push(0);
+        chlist.extend(layer.sample_type.id().to_le_bytes());
+        chlist.extend([0; 4]);
+        chlist.extend(1i32.to_le_bytes());
+        chlist.extend(1i32.to_le_bytes());
+    }
+    chlist.push(0);
+
+    let window = [0, 0, x_max, y_max].map(i32::to_le_bytes).concat();
+
+    let mut header = vec![];
+    header.extend(MAGIC);
+    header.extend(version.to_le_bytes());
+    attribute(&mut header, "channels", "chlist", &chlist);
+    attribute(&mut header, "compression", "compression", &[exr.compression.id()]);
+    attribute(&mut header, "dataWindow", "box2i", &window);
+    attribute(&mut header, "displayWindow", "box2i", &window);
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(&mut header, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+    attribute(&mut header, "screenWindowCenter", "v2f", &[0f32, 0f32].map(f32::to_le_bytes).concat());
+    attribute(&mut header, "screenWindowWidth", "float", &1f32.to_le_bytes());
+    header.push(0);
+
+    let scanlines = exr.compression.scanlines();
+    let chunks: Vec<Vec<u8>> = (0..height).step_by(scanlines)
+        .map(|y0| {
+            let mut data = vec![];
+            for y in y0..(y0 + scanlines).min(height)
+            {
+                for (_, layer, c) in &channels
+                {
+                    let n = layer.channels.len();
+                    for &sample in layer.samples[y*width*n..(y + 1)*width*n].iter().skip(*c).step_by(n)
+                    {
+                        match layer.sample_type
+                        {
+                            ExrSampleType::Half => data.extend(f16_bits(sample).to_le_bytes()),
+                            ExrSampleType::Float => data.extend(sample.to_le_bytes())
+                        }
+                    }
+                }
+            }
+            let data = match exr.compression
+            {
+                ExrCompression::None => data,
+                ExrCompression::Zip => {
+                    let compressed = zip(&data);
+                    // Readers take a block that is no smaller than its raw size to be uncompressed.
+                    if compressed.len() < data.len() {compressed} else {data}
+                }
+            };
+
+            let mut chunk = Vec::with_capacity(8 + data.len());
+            chunk.extend((y0 as i32).to_le_bytes());
+            chunk.extend((data.len() as u32).to_le_bytes());
+            chunk.extend(data);
+            chunk
+        })
+        .collect();
+
+    w.write_all(&header)?;
+    let mut offset = (header.len() + 8*chunks.len()) as u64;
+    for chunk in &chunks
+    {
+        w.write_all(&offset.to_le_bytes())?;
+        offset += chunk.len() as u64;
+    }
+    for chunk in &chunks
+    {
+        w.write_all(chunk)?;
+    }
+    Ok(())
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8])
+{
+    header.extend(name.as_bytes());
+    header.push(0);
+    header.extend(kind.as_bytes());
+    header.push(0);
+    header.extend((value.len() as i32).to_le_bytes());
+    header.extend(value);
+}
+
+/// Splits the bytes of a block into even and odd halves and delta-encodes them before deflating, as OpenEXR's ZIP compression does.
+fn zip(data: &[u8]) -> Vec<u8>
+{
+    let mut reordered: Vec<u8> = data.iter()
+        .step_by(2)
+        .chain(data.iter().skip(1).step_by(2))
+        .copied()
+        .collect();
+    let mut prev = reordered.first().copied().unwrap_or(0);
+    for x in reordered.iter_mut().skip(1)
+    {
+        let d = x.wrapping_sub(prev).wrapping_add(128);
+        prev = *x;
+        *x = d;
+    }
+    deflate::zlib_compress(&reordered)
+}
+
+/// Converts to IEEE 754 half precision, rounding to nearest even.
+fn f16_bits(x: f32) -> u16
+{
+    let bits = x.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let man = bits & 0x7fffff;
+
+    if exp == 0xff
+    {
+        return sign | 0x7c00 | if man != 0 {0x200} else {0}
+    }
+    let e = exp - 127 + 15;
+    if e >= 0x1f
+    {
+        return sign | 0x7c00
+    }
+
+    let (half, rem, halfway) = if e <= 0
+    {
+        if e < -10
+        {
+            return sign
+        }
+        let man = man | 0x800000;
+        let shift = (14 - e) as u32;
+        (man >> shift, man & ((1 << shift) - 1), 1 << (shift - 1))
+    }
+    else
+    {
+        (((e as u32) << 10) | (man >> 13), man & 0x1fff, 0x1000)
+    };
+    let round = rem > halfway || (rem == halfway && half & 1 != 0);
+    sign | (half + round as u32) as u16
+}
+
+#[cfg(test)]
+mod test
+{
+    use std::io::Cursor;
+
+    use ::exr::prelude::{f16, FlatSamples, ReadChannels, ReadLayers};
+
+    use crate::render::{AovSelection, Framebuffer, Lens, Renderer};
+    use crate::shapes::_3d::Sphere;
+
+    use super::{Exr, ExrCompression, ExrLayer, ExrSampleType};
+
+    #[test]
+    fn test_f16()
+    {
+        let mut rng = crate::tests::Rng::new(7);
+        let values = [0.0, -0.0, 1.0, -2.0, 0.1, 65504.0, 65519.0, 65520.0, 1e-5, 5.96e-8, 2.98e-8, 1e-9, f32::INFINITY, f32::NAN]
+            .into_iter()
+            .chain((0..10000).map(|_| (rng.range(-20.0, 20.0).exp2()*rng.range(-1.0, 1.0)) as f32));
+        for x in values
+        {
+            let expected = f16::from_f32(x);
+            let bits = super::f16_bits(x);
+            assert!(bits == expected.to_bits() || (x.is_nan() && f16::from_bits(bits).is_nan()), "{x}: {bits:#x} != {:#x}", expected.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_exr()
+    {
+        let renderer = Renderer::new(Lens::new([0.0, 0.0, -5.0], 2.0, 0.5), 37, 21)
+            .with_aovs(AovSelection::all());
+        let render = renderer.render(&Sphere::<f64>::new([0.0, 0.0, 0.0], 1.0), |_, raytrace| {
+            let n = raytrace.n.unwrap_or_default();
+            [n[0].abs(), n[1].abs(), n[2].abs()]
+        });
+
+        let mut sizes = vec![];
+        for compression in [ExrCompression::None, ExrCompression::Zip]
+        {
+            let exr = Exr::from_render(&render, compression)
+                .with_layer(
+                    ExrLayer::new("a_layer_with_a_rather_long_name", &Framebuffer::from_fn(37, 21, |x, y| (x*y) as f32))
+                        .with_sample_type(ExrSampleType::Float)
+                );
+            let mut data = vec![];
+            super::write_exr(&mut data, &exr).unwrap();
+            sizes.push(data.len());
+
+            let image = ::exr::prelude::read()
+                .no_deep_data()
+                .largest_resolution_level()
+                .all_channels()
+                .first_valid_layer()
+                .all_attributes()
+                .from_buffered(Cursor::new(data))
+                .unwrap();
+            assert_eq!((image.layer_data.size.0, image.layer_data.size.1), (37, 21));
+
+            let channels = &image.layer_data.channel_data.list;
+            let names: Vec<String> = channels.iter()
+                .map(|channel| channel.name.to_string())
+                .collect();
+            assert_eq!(names, [
+                "B", "G", "R",
+                "a_layer_with_a_rather_long_name.Y",
+                "depth.Z",
+                "normal.X", "normal.Y", "normal.Z",
+                "object_id.id",
+                "position.X", "position.Y", "position.Z",
+                "uv.U", "uv.V"
+            ]);
+
+            let samples = |name: &str| &channels.iter().find(|channel| channel.name.to_string() == name).unwrap().sample_data;
+            let FlatSamples::F16(red) = samples("R")
+            else
+            {
+                panic!("beauty should be stored as half floats")
+            };
+            let FlatSamples::F32(depth) = samples("depth.Z")
+            else
+            {
+                panic!("depth should be stored as floats")
+            };
+            let FlatSamples::F32(long) = samples("a_layer_with_a_rather_long_name.Y")
+            else
+            {
+                panic!("explicit float layer should be stored as floats")
+            };
+            let aov_depth = render.aovs.depth.as_ref().unwrap();
+            for y in 0..21
+            {
+                for x in 0..37
+                {
+                    let i = y*37 + x;
+                    assert_eq!(red[i], f16::from_f64(render.beauty.get(x, y).unwrap()[0]));
+                    assert_eq!(depth[i], *aov_depth.get(x, y).unwrap() as f32);
+                    assert_eq!(long[i], (x*y) as f32);
+                }
+            }
+        }
+        assert!(sizes[1] < sizes[0]);
+    }
+
+    #[test]
+    fn test_invalid()
+    {
+        let a = Framebuffer::new(2, 2, 0.0f32);
+        let b = Framebuffer::new(2, 3, 0.0f32);
+        assert!(super::write_exr(vec![], &Exr::default()).is_err());
+        assert!(super::write_exr(vec![], &Exr::default().with_layer(ExrLayer::new("a", &a)).with_layer(ExrLayer::new("b", &b))).is_err());
+        assert!(super::write_exr(vec![], &Exr::default().with_layer(ExrLayer::new("a", &a)).with_layer(ExrLayer::new("a", &a))).is_err());
+        assert!(super::write_exr(vec![], &Exr::default().with_layer(ExrLayer::new("a", &Framebuffer::new(0, 0, 0.0f32)))).is_err());
+    }
+}
\ No newline at end of file
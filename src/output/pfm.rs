@@ -0,0 +1,50 @@
+use std::io::{self, Write};
+
+use crate::render::Framebuffer;
+
+use super::{Channel, Pixel};
+
+/// Writes a little-endian PFM, keeping the full float range for HDR data. Rows are stored bottom-up, as the format requires.
+pub fn write_pfm<W, P>(mut w: W, framebuffer: &Framebuffer<P>) -> io::Result<()>
+where
+    W: Write,
+    P: Pixel
+{
+    let magic = match P::CHANNELS
+    {
+        1 => "Pf",
+        3 => "PF",
+        n => return Err(super::unsupported_channels("PFM", n))
+    };
+    write!(w, "{magic}\n{} {}\n-1.0\n", framebuffer.width(), framebuffer.height())?;
+
+    let data: Vec<u8> = framebuffer.rows()
+        .rev()
+        .flat_map(|row| row.iter().flat_map(|p| p.channels()))
+        .flat_map(|c| c.to_f32().to_le_bytes())
+        .collect();
+    w.write_all(&data)
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::render::Framebuffer;
+
+    #[test]
+    fn test_pfm()
+    {
+        let framebuffer = Framebuffer::from_fn(1, 2, |_, y| y as f32 + 0.5);
+        let mut data = vec![];
+        super::write_pfm(&mut data, &framebuffer).unwrap();
+
+        let header = b"Pf\n1 2\n-1.0\n";
+        assert_eq!(&data[..header.len()], header);
+        assert_eq!(&data[header.len()..], [1.5f32.to_le_bytes(), 0.5f32.to_le_bytes()].concat());
+
+        let framebuffer = Framebuffer::new(3, 2, [1e6, -1.0, f64::INFINITY]);
+        let mut data = vec![];
+        super::write_pfm(&mut data, &framebuffer).unwrap();
+        assert_eq!(data.len(), b"PF\n3 2\n-1.0\n".len() + 3*2*3*4);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,138 @@
+use std::io::{self, Write};
+
+use crate::render::Framebuffer;
+
+use super::{deflate, Channel, Pixel};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Writes an 8-bit PNG. Greyscale, greyscale with alpha, RGB and RGBA pixels are supported.
+///
+/// The image data is stored without compression, so no deflate implementation is needed.
+pub fn write_png<W, P>(mut w: W, framebuffer: &Framebuffer<P>) -> io::Result<()>
+where
+    W: Write,
+    P: Pixel
+{
+    let color_type = match P::CHANNELS
+    {
+        1 => 0,
+        2 => 4,
+        3 => 2,
+        4 => 6,
+        n => return Err(super::unsupported_channels("PNG", n))
+    };
+    let width = dimension(framebuffer.width())?;
+    let height = dimension(framebuffer.height())?;
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend(width.to_be_bytes());
+    ihdr.extend(height.to_be_bytes());
+    ihdr.extend([8, color_type, 0, 0, 0]);
+
+    let mut scanlines = Vec::with_capacity(framebuffer.height()*(1 + framebuffer.width()*P::CHANNELS));
+    for row in framebuffer.rows()
+    {
+        scanlines.push(0);
+        scanlines.extend(row.iter().flat_map(|p| p.channels().iter().map(|c| c.to_u8())));
+    }
+
+    w.write_all(&SIGNATURE)?;
+    write_chunk(&mut w, b"IHDR", &ihdr)?;
+    write_chunk(&mut w, b"IDAT", &deflate::zlib_stored(&scanlines))?;
+    write_chunk(&mut w, b"IEND", &[])
+}
+
+/// PNG only allows widths and heights from 1 to 2^31 - 1.
+fn dimension(n: usize) -> io::Result<u32>
+{
+    match u32::try_from(n)
+    {
+        Ok(n @ 1..=0x7fff_ffff) => Ok(n),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "PNG dimensions must be between 1 and 2^31 - 1"))
+    }
+}
+
+fn write_chunk<W>(mut w: W, kind: &[u8; 4], data: &[u8]) -> io::Result<()>
+where
+    W: Write
+{
+    let len = u32::try_from(data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk is too large"))?;
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    w.write_all(&crc32(kind.iter().chain(data)).to_be_bytes())
+}
+
+fn crc32<'a>(data: impl IntoIterator<Item = &'a u8>) -> u32
+{
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut n = 0;
+        while n < 256
+        {
+            let mut c = n as u32;
+            let mut k = 0;
+            while k < 8
+            {
+                c = if c & 1 != 0 {0xedb88320 ^ (c >> 1)} else {c >> 1};
+                k += 1;
+            }
+            table[n] = c;
+            n += 1;
+        }
+        table
+    };
+
+    !data.into_iter()
+        .fold(!0u32, |c, &b| TABLE[((c ^ b as u32) & 0xff) as usize] ^ (c >> 8))
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::render::Framebuffer;
+
+    #[test]
+    fn test_crc32()
+    {
+        assert_eq!(super::crc32(b"123456789"), 0xcbf43926);
+    }
+
+    #[test]
+    fn test_png()
+    {
+        let framebuffer = Framebuffer::from_fn(300, 200, |x, y| [x as f64/299.0, y as f64/199.0, 0.25]);
+        let mut data = vec![];
+        super::write_png(&mut data, &framebuffer).unwrap();
+
+        let image = image::load_from_memory_with_format(&data, image::ImageFormat::Png)
+            .unwrap()
+            .into_rgb8();
+        assert_eq!(image.dimensions(), (300, 200));
+        for (x, y, p) in image.enumerate_pixels()
+        {
+            let [r, g, b] = framebuffer.get(x as usize, y as usize).unwrap().map(|c| (c*255.0).round() as u8);
+            assert_eq!(p.0, [r, g, b]);
+        }
+
+        let framebuffer = Framebuffer::from_fn(3, 2, |x, y| [(x*50) as u8, (y*100) as u8]);
+        let mut data = vec![];
+        super::write_png(&mut data, &framebuffer).unwrap();
+        let image = image::load_from_memory(&data).unwrap().into_luma_alpha8();
+        assert_eq!(image.get_pixel(2, 1).0, [100, 100]);
+
+        assert!(super::write_png(vec![], &Framebuffer::new(0, 4, 0.0f32)).is_err());
+    }
+
+    #[test]
+    fn test_dimension()
+    {
+        assert!(super::dimension(0).is_err());
+        assert_eq!(super::dimension(1).unwrap(), 1);
+        assert_eq!(super::dimension(0x7fff_ffff).unwrap(), 0x7fff_ffff);
+        assert!(super::dimension(0x8000_0000).is_err());
+        assert!(super::dimension(usize::MAX).is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use std::io::{self, Write};
+
+use crate::render::Framebuffer;
+
+use super::{Channel, Pixel};
+
+/// Writes a binary PPM (`P6`) for RGB pixels, or a binary PGM (`P5`) for greyscale pixels.
+pub fn write_ppm<W, P>(mut w: W, framebuffer: &Framebuffer<P>) -> io::Result<()>
+where
+    W: Write,
+    P: Pixel
+{
+    let magic = match P::CHANNELS
+    {
+        1 => "P5",
+        3 => "P6",
+        n => return Err(super::unsupported_channels("PPM", n))
+    };
+    write!(w, "{magic}\n{} {}\n255\n", framebuffer.width(), framebuffer.height())?;
+
+    let data: Vec<u8> = framebuffer.pixels()
+        .iter()
+        .flat_map(|p| p.channels().iter().map(|c| c.to_u8()))
+        .collect();
+    w.write_all(&data)
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::render::Framebuffer;
+
+    #[test]
+    fn test_ppm()
+    {
+        let framebuffer = Framebuffer::from_fn(2, 1, |x, _| [x as f64, 0.5, 2.0]);
+        let mut data = vec![];
+        super::write_ppm(&mut data, &framebuffer).unwrap();
+        assert_eq!(data, b"P6\n2 1\n255\n\x00\x80\xff\xff\x80\xff");
+
+        let framebuffer = Framebuffer::new(1, 2, 7u8);
+        let mut data = vec![];
+        super::write_ppm(&mut data, &framebuffer).unwrap();
+        assert_eq!(data, b"P5\n1 2\n255\n\x07\x07");
+
+        assert!(super::write_ppm(vec![], &Framebuffer::new(1, 1, [0.0f32; 2])).is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,229 @@
+use core::{fmt::Debug, ops::{Add, BitAnd, BitOr, Div, Mul, Neg, Not, Sub}, simd::{prelude::*, SimdElement}};
+
+use num::Float;
+
+use crate::Ray;
+
+/// Floats that can be traced in packets of `N` rays at once, using portable SIMD.
+///
+/// Every operation is the lane-wise equivalent of the scalar one, so packet tracing gives bit-identical results.
+pub trait PacketFloat<const N: usize>: Float
+{
+    type Vector: Copy + Debug
+        + Add<Output = Self::Vector>
+        + Sub<Output = Self::Vector>
+        + Mul<Output = Self::Vector>
+        + Div<Output = Self::Vector>
+        + Neg<Output = Self::Vector>;
+    type Mask: Copy + Debug
+        + BitAnd<Output = Self::Mask>
+        + BitOr<Output = Self::Mask>
+        + Not<Output = Self::Mask>;
+
+    fn splat(x: Self) -> Self::Vector;
+    fn from_array(x: [Self; N]) -> Self::Vector;
+    fn to_array(x: Self::Vector) -> [Self; N];
+
+    fn simd_ne(a: Self::Vector, b: Self::Vector) -> Self::Mask;
+    fn simd_lt(a: Self::Vector, b: Self::Vector) -> Self::Mask;
+    fn simd_le(a: Self::Vector, b: Self::Vector) -> Self::Mask;
+    fn simd_ge(a: Self::Vector, b: Self::Vector) -> Self::Mask;
+    fn simd_is_finite(x: Self::Vector) -> Self::Mask;
+    fn simd_any(mask: Self::Mask) -> bool;
+
+    /// Takes lanes from `a` where `mask` is set, and from `b` elsewhere.
+    fn simd_select(mask: Self::Mask, a: Self::Vector, b: Self::Vector) -> Self::Vector;
+
+    /// Lane-wise square root. `core` has no vector square root, but this is usually vectorized anyway.
+    fn simd_sqrt(x: Self::Vector) -> Self::Vector
+    {
+        Self::from_array(Self::to_array(x).map(Float::sqrt))
+    }
+}
+
+macro_rules! impl_packet_float {
+    ($($f:ty),*) => {
+        $(
+            impl<const N: usize> PacketFloat<N> for $f
+            {
+                type Vector = Simd<$f, N>;
+                type Mask = Mask<<$f as SimdElement>::Mask, N>;
+
+                fn splat(x: Self) -> Self::Vector
+                {
+                    Simd::splat(x)
+                }
+                fn from_array(x: [Self; N]) -> Self::Vector
+                {
+                    Simd::from_array(x)
+                }
+                fn to_array(x: Self::Vector) -> [Self; N]
+                {
+                    x.to_array()
+                }
+
+                fn simd_ne(a: Self::Vector, b: Self::Vector) -> Self::Mask
+                {
+                    a.simd_ne(b)
+                }
+                fn simd_lt(a: Self::Vector, b: Self::Vector) -> Self::Mask
+                {
+                    a.simd_lt(b)
+                }
+                fn simd_le(a: Self::Vector, b: Self::Vector) -> Self::Mask
+                {
+                    a.simd_le(b)
+                }
+                fn simd_ge(a: Self::Vector, b: Self::Vector) -> Self::Mask
+                {
+                    a.simd_ge(b)
+                }
+                fn simd_is_finite(x: Self::Vector) -> Self::Mask
+                {
+                    x.is_finite()
+                }
+                fn simd_any(mask: Self::Mask) -> bool
+                {
+                    mask.any()
+                }
+
+                fn simd_select(mask: Self::Mask, a: Self::Vector, b: Self::Vector) -> Self::Vector
+                {
+                    mask.select(a, b)
+                }
+            }
+        )*
+    };
+}
+
+impl_packet_float!(f32, f64);
+
+/// `N` rays stored lane by lane, to be traced together. Works best when the rays are coherent, like those of neighbouring pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct RayPacket<F, const D: usize, const N: usize>
+where
+    F: PacketFloat<N>
+{
+    pub r: [F::Vector; D],
+    pub v: [F::Vector; D]
+}
+
+impl<F, const D: usize, const N: usize> RayPacket<F, D, N>
+where
+    F: PacketFloat<N>
+{
+    pub fn new(rays: &[Ray<F, D>; N]) -> Self
+    {
+        Self {
+            r: core::array::from_fn(|k| F::from_array(rays.each_ref().map(|ray| ray.r[k]))),
+            v: core::array::from_fn(|k| F::from_array(rays.each_ref().map(|ray| ray.v[k])))
+        }
+    }
+
+    pub fn rays(&self) -> [Ray<F, D>; N]
+    {
+        let r = self.r.map(F::to_array);
+        let v = self.v.map(F::to_array);
+        core::array::from_fn(|i| Ray {
+            r: r.map(|r| r[i]),
+            v: v.map(|v| v[i])
+        })
+    }
+
+    /// Lane-wise [`Ray::propagate`].
+    pub fn propagate(&self, t: F::Vector) -> [F::Vector; D]
+    {
+        core::array::from_fn(|k| self.r[k] + self.v[k]*t)
+    }
+}
+
+/// The starting value of [`Sum`](core::iter::Sum) for `F`, so that lane-wise sums round exactly like the scalar ones, down to the sign of zero.
+pub(crate) fn sum_zero<F, const N: usize>() -> F::Vector
+where
+    F: PacketFloat<N> + core::iter::Sum
+{
+    F::splat(core::iter::empty::<F>().sum())
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::iter::Sum;
+
+    use crate::{shapes::{nd::{HyperCube, HyperPlane, HyperRectangle, HyperSphere}, Shape}, tests::Rng, Ray};
+
+    use super::{PacketFloat, RayPacket};
+
+    /// Returns the number of hits.
+    fn check<F, S, const N: usize>(shape: &S, rng: &mut Rng) -> usize
+    where
+        F: PacketFloat<N> + core::fmt::Debug,
+        S: Shape<F, 3>
+    {
+        let f = |x: f64| F::from(x).unwrap();
+        let mut hits = 0;
+        for i in 0..400
+        {
+            let rays: [Ray<F, 3>; N] = core::array::from_fn(|j| {
+                let r = rng.array(-3.0, 3.0);
+                let to = rng.array::<3>(-1.0, 1.0);
+                let mut v: [f64; 3] = core::array::from_fn(|k| to[k] - r[k]);
+                // Axis-aligned and degenerate directions are where the masking has to match the scalar branches.
+                match (i + j) % 7
+                {
+                    0 => v[0] = 0.0,
+                    1 => v = [0.0, 0.0, 1.0],
+                    2 => v = [0.0; 3],
+                    _ => ()
+                }
+                Ray {
+                    r: r.map(f),
+                    v: v.map(f)
+                }
+            });
+            let packet = RayPacket::new(&rays);
+            assert_eq!(packet.rays().map(|ray| (ray.r, ray.v)), rays.map(|ray| (ray.r, ray.v)));
+
+            for (ray, raytrace) in rays.iter().zip(shape.raytrace_packet(&packet))
+            {
+                let expected = shape.raytrace(ray);
+                assert_eq!(raytrace.t.to_f64().unwrap().to_bits(), expected.t.to_f64().unwrap().to_bits(), "{ray:?}");
+                hits += expected.is_hit() as usize;
+            }
+        }
+        hits
+    }
+
+    fn check_all<F, const N: usize>(rng: &mut Rng)
+    where
+        F: PacketFloat<N> + core::fmt::Debug + Sum + core::ops::AddAssign + core::ops::DivAssign
+    {
+        let f = |x: f64| F::from(x).unwrap();
+        assert!(check::<F, _, N>(&HyperSphere::new([0.5, -0.25, 0.0].map(f), f(1.5)), rng) > 100*N);
+        check::<F, _, N>(&HyperSphere::new([0.0; 3].map(f), f(0.0)), rng);
+        assert!(check::<F, _, N>(&HyperRectangle {c1: [-1.0, -0.5, -2.0].map(f), c2: [1.5, 0.5, 1.0].map(f)}, rng) > 100*N);
+        assert!(check::<F, _, N>(&HyperCube {center: [0.25, 0.0, -0.5].map(f), radius: f(1.0)}, rng) > 100*N);
+        assert!(check::<F, _, N>(&HyperPlane {r: [0.0, -0.5, 0.0].map(f), n: [0.2, 1.0, -0.3].map(f)}, rng) > 100*N);
+        check::<F, _, N>(&HyperPlane {r: [0.0; 3].map(f), n: [0.0, 0.0, 1.0].map(f)}, rng);
+    }
+
+    #[test]
+    fn test_packets()
+    {
+        let mut rng = Rng::new(46);
+        check_all::<f64, 4>(&mut rng);
+        check_all::<f64, 8>(&mut rng);
+        check_all::<f64, 16>(&mut rng);
+        check_all::<f32, 4>(&mut rng);
+        check_all::<f32, 8>(&mut rng);
+        check_all::<f32, 16>(&mut rng);
+    }
+
+    #[test]
+    fn test_default()
+    {
+        // Shapes without their own packet implementation fall back to tracing ray by ray.
+        let shapes: Vec<HyperSphere<f64, 3>> = vec![HyperSphere::new([0.0; 3], 1.0), HyperSphere::new([1.0, 0.0, 0.0], 0.5)];
+        assert!(check::<f64, _, 8>(&shapes, &mut Rng::new(1)) > 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,334 @@
+use core::ops::{Mul, Neg};
+
+use num::Float;
+
+use crate::vec3;
+
+/// The axes of three successive rotations, either all different (Tait-Bryan angles) or with the first axis repeated (proper Euler angles).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EulerOrder
+{
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+    XYX,
+    XZX,
+    YXY,
+    YZY,
+    ZXZ,
+    ZYZ
+}
+
+impl EulerOrder
+{
+    pub const ALL: [Self; 12] = [
+        Self::XYZ, Self::XZY, Self::YXZ, Self::YZX, Self::ZXY, Self::ZYX,
+        Self::XYX, Self::XZX, Self::YXY, Self::YZY, Self::ZXZ, Self::ZYZ
+    ];
+
+    pub fn axes(self) -> [usize; 3]
+    {
+        match self
+        {
+            EulerOrder::XYZ => [0, 1, 2],
+            EulerOrder::XZY => [0, 2, 1],
+            EulerOrder::YXZ => [1, 0, 2],
+            EulerOrder::YZX => [1, 2, 0],
+            EulerOrder::ZXY => [2, 0, 1],
+            EulerOrder::ZYX => [2, 1, 0],
+            EulerOrder::XYX => [0, 1, 0],
+            EulerOrder::XZX => [0, 2, 0],
+            EulerOrder::YXY => [1, 0, 1],
+            EulerOrder::YZY => [1, 2, 1],
+            EulerOrder::ZXZ => [2, 0, 2],
+            EulerOrder::ZYZ => [2, 1, 2]
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quaternion<F>
+where
+    F: Float
+{
+    pub w: F,
+    pub x: F,
+    pub y: F,
+    pub z: F
+}
+
+impl<F> Quaternion<F>
+where
+    F: Float
+{
+    pub fn new(w: F, x: F, y: F, z: F) -> Self
+    {
+        Self {
+            w,
+            x,
+            y,
+            z
+        }
+    }
+
+    pub fn identity() -> Self
+    {
+        Self::new(F::one(), F::zero(), F::zero(), F::zero())
+    }
+
+    pub fn from_axis_angle(axis: [F; 3], theta: F) -> Self
+    {
+        let [x, y, z] = vec3::normalize(axis);
+        let half = theta/F::from(2.0).unwrap();
+        let s = half.sin();
+
+        Self::new(half.cos(), x*s, y*s, z*s)
+    }
+
+    /// Intrinsic Euler angles, where `angles[i]` is the rotation about the `i`-th axis of `order`, each applied in the frame left by the previous one.
+    pub fn from_euler(order: EulerOrder, angles: [F; 3]) -> Self
+    {
+        Self::_euler(order, angles)
+            .fold(Self::identity(), |q, dq| q*dq)
+    }
+
+    /// Extrinsic Euler angles, where `angles[i]` is the rotation about the `i`-th axis of `order`, all about the fixed axes of the world.
+    ///
+    /// This is the same as the intrinsic rotation with both `order` and `angles` reversed.
+    pub fn from_euler_extrinsic(order: EulerOrder, angles: [F; 3]) -> Self
+    {
+        Self::_euler(order, angles)
+            .fold(Self::identity(), |q, dq| dq*q)
+    }
+
+    fn _euler(order: EulerOrder, angles: [F; 3]) -> impl Iterator<Item = Self>
+    {
+        order.axes()
+            .into_iter()
+            .zip(angles)
+            .map(|(k, theta)| {
+                let mut axis = [F::zero(); 3];
+                axis[k] = F::one();
+                Self::from_axis_angle(axis, theta)
+            })
+    }
+
+    pub fn from_matrix(m: [[F; 3]; 3]) -> Self
+    {
+        let one = F::one();
+        let quarter = F::from(0.25).unwrap();
+        let [[m00, m01, m02], [m10, m11, m12], [m20, m21, m22]] = m;
+
+        let trace = m00 + m11 + m22;
+        let q = if trace > F::zero()
+        {
+            let s = (trace + one).sqrt()*F::from(2.0).unwrap();
+            Self::new(quarter*s, (m21 - m12)/s, (m02 - m20)/s, (m10 - m01)/s)
+        }
+        else if m00 > m11 && m00 > m22
+        {
+            let s = (one + m00 - m11 - m22).sqrt()*F::from(2.0).unwrap();
+            Self::new((m21 - m12)/s, quarter*s, (m01 + m10)/s, (m02 + m20)/s)
+        }
+        else if m11 > m22
+        {
+            let s = (one + m11 - m00 - m22).sqrt()*F::from(2.0).unwrap();
+            Self::new((m02 - m20)/s, (m01 + m10)/s, quarter*s, (m12 + m21)/s)
+        }
+        else
+        {
+            let s = (one + m22 - m00 - m11).sqrt()*F::from(2.0).unwrap();
+            Self::new((m10 - m01)/s, (m02 + m20)/s, (m12 + m21)/s, quarter*s)
+        };
+        q.normalize()
+    }
+
+    pub fn to_matrix(&self) -> [[F; 3]; 3]
+    {
+        let Self {w, x, y, z} = self.normalize();
+
+        let one = F::one();
+        let two = F::from(2.0).unwrap();
+
+        [
+            [one - two*(y*y + z*z), two*(x*y - w*z),       two*(x*z + w*y)],
+            [two*(x*y + w*z),       one - two*(x*x + z*z), two*(y*z - w*x)],
+            [two*(x*z - w*y),       two*(y*z + w*x),       one - two*(x*x + y*y)]
+        ]
+    }
+
+    pub fn dot(&self, rhs: &Self) -> F
+    {
+        self.w*rhs.w + self.x*rhs.x + self.y*rhs.y + self.z*rhs.z
+    }
+
+    pub fn magnitude(&self) -> F
+    {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self
+    {
+        let m = self.magnitude();
+        Self::new(self.w/m, self.x/m, self.y/m, self.z/m)
+    }
+
+    pub fn conjugate(&self) -> Self
+    {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn rotate_vec(&self, v: [F; 3]) -> [F; 3]
+    {
+        let [x, y, z] = v;
+        let Self {w: _, x, y, z} = *self*Self::new(F::zero(), x, y, z)*self.conjugate();
+        [x, y, z]
+    }
+
+    pub fn slerp(&self, rhs: &Self, t: F) -> Self
+    {
+        let lhs = self.normalize();
+        let mut rhs = rhs.normalize();
+
+        let mut cos_theta = lhs.dot(&rhs);
+        if cos_theta < F::zero()
+        {
+            rhs = -rhs;
+            cos_theta = -cos_theta;
+        }
+
+        let (a, b) = if cos_theta > F::one() - F::epsilon().sqrt()
+        {
+            (F::one() - t, t)
+        }
+        else
+        {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (((F::one() - t)*theta).sin()/sin_theta, (t*theta).sin()/sin_theta)
+        };
+
+        Self::new(
+            a*lhs.w + b*rhs.w,
+            a*lhs.x + b*rhs.x,
+            a*lhs.y + b*rhs.y,
+            a*lhs.z + b*rhs.z
+        ).normalize()
+    }
+}
+
+impl<F> Mul for Quaternion<F>
+where
+    F: Float
+{
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output
+    {
+        let Self {w: w1, x: x1, y: y1, z: z1} = self;
+        let Self {w: w2, x: x2, y: y2, z: z2} = rhs;
+        Self::new(
+            w1*w2 - x1*x2 - y1*y2 - z1*z2,
+            w1*x2 + x1*w2 + y1*z2 - z1*y2,
+            w1*y2 - x1*z2 + y1*w2 + z1*x2,
+            w1*z2 + x1*y2 - y1*x2 + z1*w2
+        )
+    }
+}
+
+impl<F> Neg for Quaternion<F>
+where
+    F: Float
+{
+    type Output = Self;
+
+    fn neg(self) -> Self::Output
+    {
+        Self::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::{FRAC_PI_2, FRAC_PI_3, FRAC_PI_4};
+
+    use crate::matrix;
+
+    use super::{EulerOrder, Quaternion};
+
+    fn assert_close<const N: usize>(a: [f64; N], b: [f64; N])
+    {
+        assert!(a.into_iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-9), "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test()
+    {
+        let q = Quaternion::from_axis_angle([0.0, 0.0, 2.0], FRAC_PI_2);
+        assert_close(q.rotate_vec([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]);
+        assert_close(matrix::mul_matrix_collumn(q.to_matrix(), [1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]);
+
+        let q = Quaternion::from_euler(EulerOrder::ZYX, [FRAC_PI_3, -FRAC_PI_4, 0.3]);
+        let Quaternion {w, x, y, z} = Quaternion::from_matrix(q.to_matrix());
+        let sign = w.signum()*q.w.signum();
+        assert_close([w, x, y, z].map(|q| q*sign), [q.w, q.x, q.y, q.z]);
+
+        let m = matrix::mul_matrix_matrix(
+            &matrix::mul_matrix_matrix(
+                &Quaternion::from_axis_angle([0.0, 0.0, 1.0], FRAC_PI_3).to_matrix(),
+                &Quaternion::from_axis_angle([0.0, 1.0, 0.0], -FRAC_PI_4).to_matrix()
+            ),
+            &Quaternion::from_axis_angle([1.0, 0.0, 0.0], 0.3).to_matrix()
+        );
+        for (a, b) in q.to_matrix().into_iter().zip(m)
+        {
+            assert_close(a, b);
+        }
+
+        let axis_matrix = |k: usize, theta: f64| {
+            let mut axis = [0.0; 3];
+            axis[k] = 1.0;
+            Quaternion::from_axis_angle(axis, theta).to_matrix()
+        };
+        let angles = [0.4, -1.1, 2.3];
+        for order in EulerOrder::ALL
+        {
+            let [r1, r2, r3] = core::array::from_fn(|i| axis_matrix(order.axes()[i], angles[i]));
+
+            let intrinsic = matrix::mul_matrix_matrix(&matrix::mul_matrix_matrix(&r1, &r2), &r3);
+            for (a, b) in Quaternion::from_euler(order, angles).to_matrix().into_iter().zip(intrinsic)
+            {
+                assert_close(a, b);
+            }
+
+            let extrinsic = matrix::mul_matrix_matrix(&matrix::mul_matrix_matrix(&r3, &r2), &r1);
+            for (a, b) in Quaternion::from_euler_extrinsic(order, angles).to_matrix().into_iter().zip(extrinsic)
+            {
+                assert_close(a, b);
+            }
+
+            let [i, j, k] = order.axes();
+            let reversed = EulerOrder::ALL.into_iter()
+                .find(|order| order.axes() == [k, j, i])
+                .unwrap();
+            let q = Quaternion::from_euler_extrinsic(order, angles);
+            let r = Quaternion::from_euler(reversed, [angles[2], angles[1], angles[0]]);
+            assert!((q.dot(&r).abs() - 1.0).abs() < 1e-9, "{order:?}");
+        }
+
+        // A quarter turn about z followed by one about the new x takes x to y and y to z
+        let q = Quaternion::from_euler(EulerOrder::ZXZ, [FRAC_PI_2, FRAC_PI_2, 0.0]);
+        assert_close(q.rotate_vec([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]);
+        assert_close(q.rotate_vec([0.0, 1.0, 0.0]), [0.0, 0.0, 1.0]);
+
+        let a = Quaternion::identity();
+        let b = Quaternion::from_axis_angle([0.0, 1.0, 0.0], FRAC_PI_2);
+        let c = a.slerp(&b, 0.5);
+        let d = Quaternion::from_axis_angle([0.0, 1.0, 0.0], FRAC_PI_4);
+        assert_close([c.w, c.x, c.y, c.z], [d.w, d.x, d.y, d.z]);
+    }
+}
\ No newline at end of file
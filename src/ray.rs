@@ -48,4 +48,56 @@ where
                 .unwrap_unchecked()
         }
     }
+}
+
+/// A ray with the reciprocal and sign of each direction component cached, so it can be tested against many boxes without dividing.
+#[derive(Debug, Clone, Copy)]
+pub struct PreparedRay<F, const D: usize>
+where
+    F: Float
+{
+    ray: Ray<F, D>,
+    v_inv: [F; D],
+    sign: [bool; D]
+}
+
+impl<F, const D: usize> PreparedRay<F, D>
+where
+    F: Float
+{
+    pub fn new(ray: Ray<F, D>) -> Self
+    {
+        Self {
+            ray,
+            v_inv: ray.v.map(F::recip),
+            sign: ray.v.map(F::is_sign_negative)
+        }
+    }
+
+    pub fn ray(&self) -> &Ray<F, D>
+    {
+        &self.ray
+    }
+
+    /// The reciprocal of each component of the direction. Zero components give an infinity of the same sign.
+    pub fn v_inv(&self) -> [F; D]
+    {
+        self.v_inv
+    }
+
+    /// Whether each component of the direction is negative.
+    pub fn sign(&self) -> [bool; D]
+    {
+        self.sign
+    }
+}
+
+impl<F, const D: usize> From<Ray<F, D>> for PreparedRay<F, D>
+where
+    F: Float
+{
+    fn from(ray: Ray<F, D>) -> Self
+    {
+        Self::new(ray)
+    }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 moddef::moddef!(
     flat(pub) mod {
+        raytrace_with_id,
         raytrace_with_norm,
         raytrace
     }
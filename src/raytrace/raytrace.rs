@@ -9,6 +9,7 @@ pub struct Raytrace<F, const D: usize>
 where
     F: Float
 {
+    /// The ray parameter at the hit, i.e. the hit lies at `ray.propagate(t)`. This equals the distance only if `ray.v` is normalized.
     pub t: F
 }
 
@@ -53,7 +54,10 @@ where
             {
                 true => Some(norm()),
                 false => None
-            }
+            },
+            uv: None,
+            tangent: None,
+            bitangent: None
         }
     }
 
@@ -0,0 +1,109 @@
+use core::ops::{Deref, DerefMut};
+
+use num::Float;
+
+use super::RaytraceWithNorm;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectId(pub(crate) usize);
+
+impl ObjectId
+{
+    pub fn index(&self) -> usize
+    {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaytraceWithId<F, const D: usize>
+where
+    F: Float
+{
+    pub raytrace: RaytraceWithNorm<F, D>,
+    pub id: Option<ObjectId>
+}
+
+impl<F, const D: usize> Deref for RaytraceWithId<F, D>
+where
+    F: Float
+{
+    type Target = RaytraceWithNorm<F, D>;
+
+    fn deref(&self) -> &Self::Target
+    {
+        &self.raytrace
+    }
+}
+impl<F, const D: usize> DerefMut for RaytraceWithId<F, D>
+where
+    F: Float
+{
+    fn deref_mut(&mut self) -> &mut Self::Target
+    {
+        &mut self.raytrace
+    }
+}
+
+impl<F, const D: usize> PartialOrd for RaytraceWithId<F, D>
+where
+    F: Float
+{
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering>
+    {
+        self.raytrace.partial_cmp(&other.raytrace)
+    }
+}
+
+impl<F, const D: usize> RaytraceWithId<F, D>
+where
+    F: Float
+{
+    pub fn miss() -> Self
+    {
+        Self {
+            raytrace: RaytraceWithNorm::miss(),
+            id: None
+        }
+    }
+
+    pub fn is_hit(&self) -> bool
+    {
+        self.raytrace.is_hit()
+    }
+
+    pub fn is_miss(&self) -> bool
+    {
+        !self.is_hit()
+    }
+
+    pub fn without_id(&self) -> RaytraceWithNorm<F, D>
+    {
+        let Self {raytrace, id: _} = self;
+        *raytrace
+    }
+
+    pub fn min(self, rhs: Self) -> Self
+    {
+        if self > rhs
+        {
+            rhs
+        }
+        else
+        {
+            self
+        }
+    }
+
+    pub fn max(self, rhs: Self) -> Self
+    {
+        if self < rhs
+        {
+            rhs
+        }
+        else
+        {
+            self
+        }
+    }
+}
\ No newline at end of file
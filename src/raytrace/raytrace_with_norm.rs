@@ -2,7 +2,9 @@ use core::ops::{Deref, DerefMut};
 
 use num::Float;
 
-use super::Raytrace;
+use crate::texture::Texture;
+
+use super::{ObjectId, Raytrace, RaytraceWithId};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RaytraceWithNorm<F, const D: usize>
@@ -10,7 +12,10 @@ where
     F: Float
 {
     pub raytrace: Raytrace<F, D>,
-    pub n: Option<[F; D]>
+    pub n: Option<[F; D]>,
+    pub uv: Option<[F; 2]>,
+    pub tangent: Option<[F; D]>,
+    pub bitangent: Option<[F; D]>
 }
 
 impl<F, const D: usize> Deref for RaytraceWithNorm<F, D>
@@ -52,7 +57,10 @@ where
     {
         Self {
             raytrace: Raytrace::miss(),
-            n: None
+            n: None,
+            uv: None,
+            tangent: None,
+            bitangent: None
         }
     }
 
@@ -68,16 +76,90 @@ where
 
     pub fn without_norm(&self) -> Raytrace<F, D>
     {
-        let Self {raytrace, n: _} = self;   
+        let Self {raytrace, ..} = self;
         *raytrace
     }
 
+    pub fn with_id(self, id: ObjectId) -> RaytraceWithId<F, D>
+    {
+        RaytraceWithId {
+            raytrace: self,
+            id: match self.is_hit()
+            {
+                true => Some(id),
+                false => None
+            }
+        }
+    }
+
+    pub fn with_uv(self, uv: impl FnOnce() -> [F; 2]) -> Self
+    {
+        Self {
+            uv: match self.is_hit()
+            {
+                true => Some(uv()),
+                false => None
+            },
+            ..self
+        }
+    }
+
+    /// Sets the tangent and bitangent, which follow the directions of increasing `u` and `v` respectively.
+    pub fn with_tangents(self, tangents: impl FnOnce() -> [[F; D]; 2]) -> Self
+    {
+        let [tangent, bitangent] = match self.is_hit()
+        {
+            true => tangents().map(Some),
+            false => [None; 2]
+        };
+        Self {
+            tangent,
+            bitangent,
+            ..self
+        }
+    }
+
     pub fn map_norm(self, map: impl FnOnce([F; D]) -> [F; D]) -> RaytraceWithNorm<F, D>
     {
-        let Self {raytrace, n} = self;
         RaytraceWithNorm {
-            raytrace,
-            n: n.map(map)
+            n: self.n.map(map),
+            ..self
+        }
+    }
+
+    pub fn map_tangents(self, mut map: impl FnMut([F; D]) -> [F; D]) -> RaytraceWithNorm<F, D>
+    {
+        RaytraceWithNorm {
+            tangent: self.tangent.map(&mut map),
+            bitangent: self.bitangent.map(&mut map),
+            ..self
+        }
+    }
+
+    /// Perturbs the shading normal by a tangent-space normal, sampled at the `uv` of the hit, whose components are along the tangent, bitangent and normal.
+    pub fn with_normal_map<X>(self, normal_map: &X) -> Self
+    where
+        X: Texture<F, [F; 3]> + ?Sized
+    {
+        let (Some(n), Some(uv), Some(tangent), Some(bitangent)) = (self.n, self.uv, self.tangent, self.bitangent)
+        else
+        {
+            return self
+        };
+
+        let [x, y, z] = normal_map.sample(uv);
+        let n: [F; D] = core::array::from_fn(|i| tangent[i]*x + bitangent[i]*y + n[i]*z);
+        let n_norm = n.into_iter()
+            .map(|n| n*n)
+            .fold(F::zero(), |a, b| a + b)
+            .sqrt();
+        if n_norm > F::zero()
+        {
+            self.map_norm(|_| n.map(|n| n/n_norm))
+        }
+        else
+        {
+            self
         }
     }
 
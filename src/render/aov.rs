@@ -0,0 +1,134 @@
+use num::Float;
+
+use crate::{Ray, RaytraceWithId};
+
+use super::Framebuffer;
+
+/// Which arbitrary output variables to render alongside the shaded image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AovSelection
+{
+    pub depth: bool,
+    pub normal: bool,
+    pub position: bool,
+    pub object_id: bool,
+    pub uv: bool
+}
+
+impl AovSelection
+{
+    pub fn all() -> Self
+    {
+        Self {
+            depth: true,
+            normal: true,
+            position: true,
+            object_id: true,
+            uv: true
+        }
+    }
+}
+
+/// The output variables of a single pixel. Pixels that miss have infinite depth, an object ID of `-1` and zero for everything else.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AovSample<F>
+where
+    F: Float
+{
+    pub depth: F,
+    pub normal: [F; 3],
+    pub position: [F; 3],
+    pub object_id: F,
+    pub uv: [F; 2]
+}
+
+impl<F> AovSample<F>
+where
+    F: Float
+{
+    pub fn miss() -> Self
+    {
+        Self {
+            depth: F::infinity(),
+            normal: [F::zero(); 3],
+            position: [F::zero(); 3],
+            object_id: -F::one(),
+            uv: [F::zero(); 2]
+        }
+    }
+
+    pub fn new(ray: &Ray<F, 3>, raytrace: &RaytraceWithId<F, 3>) -> Self
+    {
+        if raytrace.is_miss()
+        {
+            return Self::miss()
+        }
+        Self {
+            depth: raytrace.t,
+            normal: raytrace.n.unwrap_or([F::zero(); 3]),
+            position: ray.propagate(raytrace.t),
+            object_id: raytrace.id
+                .and_then(|id| F::from(id.index()))
+                .unwrap_or(-F::one()),
+            uv: raytrace.uv.unwrap_or([F::zero(); 2])
+        }
+    }
+}
+
+/// The selected output variables of a whole image, as separate float buffers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aovs<F>
+where
+    F: Float
+{
+    pub depth: Option<Framebuffer<F>>,
+    pub normal: Option<Framebuffer<[F; 3]>>,
+    pub position: Option<Framebuffer<[F; 3]>>,
+    pub object_id: Option<Framebuffer<F>>,
+    pub uv: Option<Framebuffer<[F; 2]>>
+}
+
+impl<F> Aovs<F>
+where
+    F: Float
+{
+    pub fn new(selection: AovSelection, width: usize, height: usize) -> Self
+    {
+        let miss = AovSample::<F>::miss();
+        Self {
+            depth: selection.depth.then(|| Framebuffer::new(width, height, miss.depth)),
+            normal: selection.normal.then(|| Framebuffer::new(width, height, miss.normal)),
+            position: selection.position.then(|| Framebuffer::new(width, height, miss.position)),
+            object_id: selection.object_id.then(|| Framebuffer::new(width, height, miss.object_id)),
+            uv: selection.uv.then(|| Framebuffer::new(width, height, miss.uv))
+        }
+    }
+
+    pub fn selection(&self) -> AovSelection
+    {
+        AovSelection {
+            depth: self.depth.is_some(),
+            normal: self.normal.is_some(),
+            position: self.position.is_some(),
+            object_id: self.object_id.is_some(),
+            uv: self.uv.is_some()
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, sample: AovSample<F>)
+    {
+        fn set<T>(buffer: &mut Option<Framebuffer<T>>, x: usize, y: usize, value: T)
+        {
+            if let Some(dst) = buffer.as_mut().and_then(|buffer| buffer.get_mut(x, y))
+            {
+                *dst = value
+            }
+        }
+
+        set(&mut self.depth, x, y, sample.depth);
+        set(&mut self.normal, x, y, sample.normal);
+        set(&mut self.position, x, y, sample.position);
+        set(&mut self.object_id, x, y, sample.object_id);
+        set(&mut self.uv, x, y, sample.uv);
+    }
+}
\ No newline at end of file
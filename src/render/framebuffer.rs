@@ -0,0 +1,100 @@
+use alloc::vec::Vec;
+
+/// A rectangular grid of pixels, stored row by row from the top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Framebuffer<T>
+{
+    width: usize,
+    height: usize,
+    pixels: Vec<T>
+}
+
+impl<T> Framebuffer<T>
+{
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone
+    {
+        Self {
+            width,
+            height,
+            pixels: alloc::vec![fill; width*height]
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Self
+    {
+        Self {
+            width,
+            height,
+            pixels: (0..height).flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| f(x, y))
+                .collect()
+        }
+    }
+
+    pub fn width(&self) -> usize
+    {
+        self.width
+    }
+
+    pub fn height(&self) -> usize
+    {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[T]
+    {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [T]
+    {
+        &mut self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<T>
+    {
+        self.pixels
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T>
+    {
+        if x < self.width
+        {
+            self.pixels.get(y*self.width + x)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T>
+    {
+        if x < self.width
+        {
+            self.pixels.get_mut(y*self.width + x)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator
+    {
+        self.pixels.chunks(self.width.max(1))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Framebuffer<U>
+    {
+        Framebuffer {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.iter()
+                .map(f)
+                .collect()
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+use num::Float;
+
+use crate::{vec3, Ray};
+
+/// A lens looking along the z-axis. Rays start on a square of half-width `size` around `r`, and fan out by `bend` towards the edges, so a `size` of zero
+/// gives a pinhole camera and a `bend` of zero gives an orthographic one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lens<F>
+where
+    F: Float
+{
+    pub r: [F; 3],
+    pub size: F,
+    pub bend: F
+}
+
+impl<F> Lens<F>
+where
+    F: Float
+{
+    pub fn new(r: [F; 3], size: F, bend: F) -> Self
+    {
+        Self {
+            r,
+            size,
+            bend
+        }
+    }
+
+    /// The ray through the point `[x, y]` of the lens, where both range from `-1` to `1` across it.
+    pub fn ray(&self, x: F, y: F) -> Ray<F, 3>
+    {
+        let [lens_x, lens_y, lens_z] = self.r;
+        Ray {
+            r: [x*self.size + lens_x, y*self.size + lens_y, lens_z],
+            v: vec3::normalize([x*self.bend, y*self.bend, F::one()])
+        }
+    }
+}
\ No newline at end of file
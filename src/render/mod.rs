@@ -0,0 +1,11 @@
+moddef::moddef!(
+    flat(pub) mod {
+        aov,
+        framebuffer,
+        lens,
+        progressive,
+        renderer,
+        tile,
+        tonemap
+    }
+);
\ No newline at end of file
@@ -0,0 +1,242 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use alloc::vec::Vec;
+
+use num::Float;
+
+use crate::{shapes::Shape, Ray, RaytraceWithId};
+
+use super::{renderer::TilePixels, Aovs, Framebuffer, Render, Renderer, Tile};
+
+/// Renders in passes, each with every ray shifted to a different point within its pixel, and averages them into an increasingly anti-aliased estimate.
+///
+/// The first pass goes through pixel centers, so it matches [`Renderer::render`]. Output variables other than the shaded color are taken from the first pass.
+pub struct ProgressiveRenderer<'a, F, S, C>
+where
+    F: Float,
+    S: ?Sized
+{
+    renderer: Renderer<F>,
+    shape: &'a S,
+    shader: C,
+    sum: Framebuffer<[F; 3]>,
+    aovs: Aovs<F>,
+    passes: usize
+}
+
+impl<'a, F, S, C> ProgressiveRenderer<'a, F, S, C>
+where
+    F: Float,
+    S: Shape<F, 3> + ?Sized,
+    C: Fn(&Ray<F, 3>, &RaytraceWithId<F, 3>) -> [F; 3]
+{
+    pub fn new(renderer: Renderer<F>, shape: &'a S, shader: C) -> Self
+    {
+        Self {
+            renderer,
+            shape,
+            shader,
+            sum: Framebuffer::new(renderer.width, renderer.height, [F::zero(); 3]),
+            aovs: Aovs::new(renderer.aovs, renderer.width, renderer.height),
+            passes: 0
+        }
+    }
+
+    pub fn renderer(&self) -> &Renderer<F>
+    {
+        &self.renderer
+    }
+
+    /// The number of completed passes.
+    pub fn passes(&self) -> usize
+    {
+        self.passes
+    }
+
+    /// The point within each pixel that rays go through on a given pass, following the R2 low-discrepancy sequence from the center.
+    pub fn offset(pass: usize) -> [F; 2]
+    {
+        const A: [f64; 2] = [0.7548776662466927, 0.5698402909980532];
+        A.map(|a| F::from((0.5 + a*pass as f64).fract()).unwrap())
+    }
+
+    /// The average of all completed passes, or black if there are none.
+    pub fn estimate(&self) -> Framebuffer<[F; 3]>
+    {
+        let n = F::from(self.passes.max(1)).unwrap();
+        self.sum.map(|c| c.map(|c| c/n))
+    }
+
+    pub fn render(&self) -> Render<F>
+    {
+        Render {
+            beauty: self.estimate(),
+            aovs: self.aovs.clone()
+        }
+    }
+
+    /// Renders one more pass, checking `cancel` before each tile.
+    ///
+    /// Returns `false` if the pass was cancelled, in which case it is discarded, and calling this again starts it over.
+    pub fn pass(&mut self, cancel: &AtomicBool) -> bool
+    {
+        let offset = Self::offset(self.passes);
+        let mut tiles = Vec::new();
+        for tile in self.renderer.tiles()
+        {
+            if cancel.load(Ordering::Relaxed)
+            {
+                return false
+            }
+            tiles.push((tile, self.renderer.render_tile_at(self.shape, &self.shader, tile, offset)));
+        }
+        self.accumulate(tiles);
+        true
+    }
+
+    /// Like [`pass`](Self::pass), but renders tiles in parallel on the current rayon thread pool.
+    #[cfg(feature = "rayon")]
+    pub fn pass_parallel(&mut self, cancel: &AtomicBool) -> bool
+    where
+        F: Send + Sync,
+        S: Sync,
+        C: Sync
+    {
+        use rayon::prelude::*;
+
+        let offset = Self::offset(self.passes);
+        let tiles: Vec<Tile> = self.renderer.tiles().collect();
+        let pixels: Option<Vec<_>> = tiles.par_iter()
+            .map(|&tile| (!cancel.load(Ordering::Relaxed)).then(|| self.renderer.render_tile_at(self.shape, &self.shader, tile, offset)))
+            .collect();
+        match pixels
+        {
+            Some(pixels) => {
+                self.accumulate(tiles.into_iter().zip(pixels));
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Renders passes until there are `passes` of them, calling `on_pass` after each, or until cancelled.
+    ///
+    /// Returns whether all passes completed. A cancelled run can be resumed by calling this again.
+    pub fn run(&mut self, passes: usize, cancel: &AtomicBool, mut on_pass: impl FnMut(&Self)) -> bool
+    {
+        while self.passes < passes
+        {
+            if !self.pass(cancel)
+            {
+                return false
+            }
+            on_pass(self);
+        }
+        true
+    }
+
+    fn accumulate(&mut self, tiles: impl IntoIterator<Item = (Tile, TilePixels<F>)>)
+    {
+        for (tile, pixels) in tiles
+        {
+            for ((x, y), (color, sample)) in tile.pixels().zip(pixels)
+            {
+                let sum = self.sum.get_mut(x, y).unwrap();
+                for (sum, c) in sum.iter_mut().zip(color)
+                {
+                    *sum = *sum + c;
+                }
+                if self.passes == 0
+                {
+                    self.aovs.set(x, y, sample);
+                }
+            }
+        }
+        self.passes += 1;
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    use crate::{render::{AovSelection, Lens, Renderer}, shapes::_3d::Sphere, Ray, RaytraceWithId};
+
+    use super::ProgressiveRenderer;
+
+    fn shade(_: &Ray<f64, 3>, raytrace: &RaytraceWithId<f64, 3>) -> [f64; 3]
+    {
+        if raytrace.is_hit() {[1.0; 3]} else {[0.0; 3]}
+    }
+
+    #[test]
+    fn test_progressive()
+    {
+        let sphere = Sphere::new([0.0, 0.0, 0.0], 1.0);
+        let renderer = Renderer::new(Lens::new([0.0, 0.0, -5.0], 1.5, 0.0), 16, 16)
+            .with_aovs(AovSelection::all())
+            .with_tile_size(4);
+        let mut progressive = ProgressiveRenderer::new(renderer, &sphere, shade);
+        let never = AtomicBool::new(false);
+
+        assert!(progressive.pass(&never));
+        assert_eq!(progressive.render(), renderer.render(&sphere, shade));
+
+        let mut estimates = vec![];
+        assert!(progressive.run(64, &never, |progressive| estimates.push(progressive.estimate())));
+        assert_eq!(progressive.passes(), 64);
+        assert_eq!(estimates.len(), 63);
+
+        // Edge pixels become partially covered, and the coverage of the whole image approaches the area of the sphere.
+        let estimate = progressive.estimate();
+        assert!(estimate.pixels().iter().any(|c| c[0] > 0.1 && c[0] < 0.9));
+        let area = estimate.pixels().iter().map(|c| c[0]).sum::<f64>()*(3.0/16.0f64).powi(2);
+        assert!((area - core::f64::consts::PI).abs() < 0.05, "{area}");
+    }
+
+    #[test]
+    fn test_cancel()
+    {
+        let sphere = Sphere::new([0.0, 0.0, 0.0], 1.0);
+        let renderer = Renderer::new(Lens::new([0.0, 0.0, -5.0], 1.5, 0.0), 16, 16)
+            .with_tile_size(4);
+
+        let mut uninterrupted = ProgressiveRenderer::new(renderer, &sphere, shade);
+        assert!(uninterrupted.run(8, &AtomicBool::new(false), |_| {}));
+
+        let cancel = AtomicBool::new(true);
+        let mut progressive = ProgressiveRenderer::new(renderer, &sphere, shade);
+        assert!(!progressive.pass(&cancel));
+        assert_eq!(progressive.passes(), 0);
+
+        cancel.store(false, Ordering::Relaxed);
+        assert!(!progressive.run(8, &cancel, |progressive| if progressive.passes() == 3 {cancel.store(true, Ordering::Relaxed)}));
+        assert_eq!(progressive.passes(), 3);
+
+        cancel.store(false, Ordering::Relaxed);
+        assert!(progressive.run(8, &cancel, |_| {}));
+        assert_eq!(progressive.estimate(), uninterrupted.estimate());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel()
+    {
+        let sphere = Sphere::new([0.0, 0.0, 0.0], 1.0);
+        let renderer = Renderer::new(Lens::new([0.0, 0.0, -5.0], 1.5, 0.0), 16, 16)
+            .with_tile_size(4);
+
+        let never = AtomicBool::new(false);
+        let mut serial = ProgressiveRenderer::new(renderer, &sphere, shade);
+        let mut parallel = ProgressiveRenderer::new(renderer, &sphere, shade);
+        for _ in 0..4
+        {
+            assert!(serial.pass(&never));
+            assert!(parallel.pass_parallel(&never));
+        }
+        assert_eq!(serial.render(), parallel.render());
+        assert!(!parallel.pass_parallel(&AtomicBool::new(true)));
+        assert_eq!(parallel.passes(), 4);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,314 @@
+use alloc::vec::Vec;
+
+use num::Float;
+
+use crate::{shapes::Shape, Ray, RaytraceWithId};
+
+use super::{AovSample, AovSelection, Aovs, Framebuffer, Lens, Progress, Tile};
+
+/// The shaded color and output variables of each pixel of a tile, in the order of [`Tile::pixels`].
+pub(super) type TilePixels<F> = Vec<([F; 3], AovSample<F>)>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Render<F>
+where
+    F: Float
+{
+    pub beauty: Framebuffer<[F; 3]>,
+    pub aovs: Aovs<F>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Renderer<F>
+where
+    F: Float
+{
+    pub lens: Lens<F>,
+    pub width: usize,
+    pub height: usize,
+    pub aovs: AovSelection,
+    pub tile_size: usize
+}
+
+impl<F> Renderer<F>
+where
+    F: Float
+{
+    pub fn new(lens: Lens<F>, width: usize, height: usize) -> Self
+    {
+        Self {
+            lens,
+            width,
+            height,
+            aovs: AovSelection::default(),
+            tile_size: 32
+        }
+    }
+
+    pub fn with_aovs(self, aovs: AovSelection) -> Self
+    {
+        Self {
+            aovs,
+            ..self
+        }
+    }
+
+    pub fn with_tile_size(self, tile_size: usize) -> Self
+    {
+        Self {
+            tile_size,
+            ..self
+        }
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = Tile>
+    {
+        Tile::split(self.width, self.height, self.tile_size)
+    }
+
+    /// The ray through the center of a pixel, with the lens spanning the width of the image.
+    pub fn ray(&self, x: usize, y: usize) -> Ray<F, 3>
+    {
+        let half = F::from(0.5).unwrap();
+        self.ray_at(x, y, [half, half])
+    }
+
+    /// The ray through a point within a pixel, where `offset` ranges from `[0, 0]` at its top left corner to `[1, 1]` at its bottom right.
+    pub fn ray_at(&self, x: usize, y: usize, offset: [F; 2]) -> Ray<F, 3>
+    {
+        let two = F::from(2.0).unwrap();
+        let width = F::from(self.width).unwrap();
+        let height = F::from(self.height).unwrap();
+
+        let x = (F::from(x).unwrap() + offset[0])/width*two - F::one();
+        let y = (F::one() - (F::from(y).unwrap() + offset[1])/height*two)*height/width;
+        self.lens.ray(x, y)
+    }
+
+    pub fn render_pixel<S, C>(&self, shape: &S, shader: &C, x: usize, y: usize) -> ([F; 3], AovSample<F>)
+    where
+        S: Shape<F, 3> + ?Sized,
+        C: Fn(&Ray<F, 3>, &RaytraceWithId<F, 3>) -> [F; 3] + ?Sized
+    {
+        let ray = self.ray(x, y);
+        self.render_ray(shape, shader, &ray)
+    }
+
+    fn render_ray<S, C>(&self, shape: &S, shader: &C, ray: &Ray<F, 3>) -> ([F; 3], AovSample<F>)
+    where
+        S: Shape<F, 3> + ?Sized,
+        C: Fn(&Ray<F, 3>, &RaytraceWithId<F, 3>) -> [F; 3] + ?Sized
+    {
+        let raytrace = shape.raytrace_with_id(ray);
+        (shader(ray, &raytrace), AovSample::new(ray, &raytrace))
+    }
+
+    /// Renders `shape`, letting `shader` decide the color of each pixel from its ray and what it hit, if anything.
+    pub fn render<S, C>(&self, shape: &S, shader: C) -> Render<F>
+    where
+        S: Shape<F, 3> + ?Sized,
+        C: Fn(&Ray<F, 3>, &RaytraceWithId<F, 3>) -> [F; 3]
+    {
+        let mut aovs = Aovs::new(self.aovs, self.width, self.height);
+        let beauty = Framebuffer::from_fn(self.width, self.height, |x, y| {
+            let (color, sample) = self.render_pixel(shape, &shader, x, y);
+            aovs.set(x, y, sample);
+            color
+        });
+
+        Render {
+            beauty,
+            aovs
+        }
+    }
+
+    fn render_tile<S, C>(&self, shape: &S, shader: &C, tile: Tile) -> TilePixels<F>
+    where
+        S: Shape<F, 3> + ?Sized,
+        C: Fn(&Ray<F, 3>, &RaytraceWithId<F, 3>) -> [F; 3] + ?Sized
+    {
+        let half = F::from(0.5).unwrap();
+        self.render_tile_at(shape, shader, tile, [half, half])
+    }
+
+    /// Renders a tile with every ray going through the same point within its pixel.
+    pub(super) fn render_tile_at<S, C>(&self, shape: &S, shader: &C, tile: Tile, offset: [F; 2]) -> TilePixels<F>
+    where
+        S: Shape<F, 3> + ?Sized,
+        C: Fn(&Ray<F, 3>, &RaytraceWithId<F, 3>) -> [F; 3] + ?Sized
+    {
+        tile.pixels()
+            .map(|(x, y)| self.render_ray(shape, shader, &self.ray_at(x, y, offset)))
+            .collect()
+    }
+
+    fn assemble(&self, tiles: impl IntoIterator<Item = (Tile, TilePixels<F>)>) -> Render<F>
+    {
+        let mut beauty = Framebuffer::new(self.width, self.height, [F::zero(); 3]);
+        let mut aovs = Aovs::new(self.aovs, self.width, self.height);
+        for (tile, pixels) in tiles
+        {
+            for ((x, y), (color, sample)) in tile.pixels().zip(pixels)
+            {
+                *beauty.get_mut(x, y).unwrap() = color;
+                aovs.set(x, y, sample);
+            }
+        }
+
+        Render {
+            beauty,
+            aovs
+        }
+    }
+
+    /// Like [`render`](Self::render), but one tile at a time, calling `progress` after each tile.
+    pub fn render_tiles<S, C>(&self, shape: &S, shader: C, mut progress: impl FnMut(Progress)) -> Render<F>
+    where
+        S: Shape<F, 3> + ?Sized,
+        C: Fn(&Ray<F, 3>, &RaytraceWithId<F, 3>) -> [F; 3]
+    {
+        let tiles_total = self.tiles().count();
+        self.assemble(self.tiles()
+            .enumerate()
+            .map(|(i, tile)| {
+                let pixels = self.render_tile(shape, &shader, tile);
+                progress(Progress {
+                    tile,
+                    tiles_done: i + 1,
+                    tiles_total
+                });
+                (tile, pixels)
+            })
+            .collect::<Vec<_>>()
+        )
+    }
+
+    /// Renders tiles in parallel on the current rayon thread pool.
+    ///
+    /// Every pixel is rendered independently, so the result is identical to [`render`](Self::render) regardless of the number of threads.
+    /// `progress` is called after each tile, one call at a time, with `tiles_done` counting up, but the tiles may finish in any order.
+    #[cfg(feature = "rayon")]
+    pub fn render_parallel<S, C>(&self, shape: &S, shader: C, progress: impl FnMut(Progress) + Send) -> Render<F>
+    where
+        F: Send + Sync,
+        S: Shape<F, 3> + Sync + ?Sized,
+        C: Fn(&Ray<F, 3>, &RaytraceWithId<F, 3>) -> [F; 3] + Sync
+    {
+        use rayon::prelude::*;
+        use std::sync::Mutex;
+
+        let tiles: Vec<Tile> = self.tiles().collect();
+        let tiles_total = tiles.len();
+        let progress = Mutex::new((0, progress));
+
+        let pixels: Vec<_> = tiles.par_iter()
+            .map(|&tile| {
+                let pixels = self.render_tile(shape, &shader, tile);
+                let mut progress = progress.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let (tiles_done, callback) = &mut *progress;
+                *tiles_done += 1;
+                callback(Progress {
+                    tile,
+                    tiles_done: *tiles_done,
+                    tiles_total
+                });
+                pixels
+            })
+            .collect();
+
+        self.assemble(tiles.into_iter().zip(pixels))
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::{render::{AovSelection, Lens, Renderer}, shapes::{Scene, Shape, _3d::{Cube, Sphere}}, Ray, RaytraceWithId};
+
+    #[test]
+    fn test()
+    {
+        let mut scene = Scene::<f64, 3>::new();
+        scene.insert(Box::new(Cube {center: [-2.0, 0.0, 0.0], radius: 1.0}), ());
+        let sphere = scene.insert(Box::new(Sphere::new([2.0, 0.0, 0.0], 1.0)), ());
+
+        let renderer = Renderer::new(Lens::new([0.0, 0.0, -5.0], 4.0, 0.0), 8, 4)
+            .with_aovs(AovSelection::all());
+        let render = renderer.render(&scene, |_, raytrace| if raytrace.is_hit() {[1.0; 3]} else {[0.0; 3]});
+
+        assert_eq!(render.beauty.width(), 8);
+        assert_eq!(render.beauty.height(), 4);
+
+        let depth = render.aovs.depth.as_ref().unwrap();
+        let object_id = render.aovs.object_id.as_ref().unwrap();
+        let position = render.aovs.position.as_ref().unwrap();
+        let normal = render.aovs.normal.as_ref().unwrap();
+
+        // The lens spans [-4, 4] across 8 pixels, so the pixels on row 1 are centered at y = 0.5 and x = -3.5 + x_pixel
+        assert_eq!(*render.beauty.get(6, 1).unwrap(), [1.0; 3]);
+        assert_eq!(*object_id.get(6, 1).unwrap(), sphere.index() as f64);
+        assert_eq!(*object_id.get(1, 1).unwrap(), 0.0);
+        assert_eq!(*object_id.get(3, 1).unwrap(), -1.0);
+        assert_eq!(*depth.get(3, 1).unwrap(), f64::INFINITY);
+
+        let [x, y, z] = *position.get(6, 1).unwrap();
+        assert!(((x - 2.0).powi(2) + y*y + z*z - 1.0).abs() < 1e-9);
+        assert!((depth.get(6, 1).unwrap() - (z + 5.0)).abs() < 1e-9);
+        assert!(normal.get(6, 1).unwrap()[2] < 0.0);
+        assert!(render.aovs.uv.is_some());
+
+        let render = Renderer {aovs: AovSelection::default(), ..renderer}.render(&scene, |_, _| [0.0; 3]);
+        assert!(render.aovs.depth.is_none() && render.aovs.uv.is_none());
+    }
+
+    fn tiles_scene() -> (Scene<f64, 3, (), dyn Shape<f64, 3> + Send + Sync>, Renderer<f64>)
+    {
+        let mut scene = Scene::new();
+        scene.insert(Box::new(Cube {center: [-2.0, 0.5, 1.0], radius: 1.0}) as Box<dyn Shape<f64, 3> + Send + Sync>, ());
+        scene.insert(Box::new(Sphere::new([1.0, -0.5, 0.0], 1.5)), ());
+
+        let renderer = Renderer::new(Lens::new([0.0, 0.0, -5.0], 3.0, 0.7), 53, 29)
+            .with_aovs(AovSelection::all())
+            .with_tile_size(8);
+        (scene, renderer)
+    }
+
+    fn shade(_: &Ray<f64, 3>, raytrace: &RaytraceWithId<f64, 3>) -> [f64; 3]
+    {
+        raytrace.n.map(|n| n.map(|n| n*0.5 + 0.5)).unwrap_or([0.1; 3])
+    }
+
+    #[test]
+    fn test_tiles()
+    {
+        let (scene, renderer) = tiles_scene();
+        let expected = renderer.render(&scene, shade);
+
+        let mut reports = vec![];
+        let render = renderer.render_tiles(&scene, shade, |progress| reports.push(progress));
+        assert_eq!(render, expected);
+        assert_eq!(reports.len(), 7*4);
+        assert!(reports.iter().enumerate().all(|(i, progress)| progress.tiles_done == i + 1 && progress.tiles_total == 28));
+        assert_eq!(reports.last().unwrap().fraction(), 1.0);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel()
+    {
+        let (scene, renderer) = tiles_scene();
+        let expected = renderer.render(&scene, shade);
+
+        for threads in [1, 2, 7]
+        {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let mut done = vec![];
+            let render = pool.install(|| renderer.render_parallel(&scene, shade, |progress| done.push(progress.tiles_done)));
+            assert_eq!(render, expected);
+            assert_eq!(done, (1..=28).collect::<Vec<_>>());
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+/// A rectangular region of the image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tile
+{
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize
+}
+
+impl Tile
+{
+    /// Splits a `width` by `height` image into tiles of at most `size` by `size` pixels, row by row from the top.
+    pub fn split(width: usize, height: usize, size: usize) -> impl Iterator<Item = Tile>
+    {
+        let size = size.max(1);
+        (0..height).step_by(size)
+            .flat_map(move |y| (0..width).step_by(size).map(move |x| Tile {
+                x,
+                y,
+                width: size.min(width - x),
+                height: size.min(height - y)
+            }))
+    }
+
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize)>
+    {
+        let Tile {x, y, width, height} = *self;
+        (y..y + height).flat_map(move |y| (x..x + width).map(move |x| (x, y)))
+    }
+}
+
+/// Reported each time a tile is finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress
+{
+    pub tile: Tile,
+    pub tiles_done: usize,
+    pub tiles_total: usize
+}
+
+impl Progress
+{
+    pub fn fraction(&self) -> f64
+    {
+        self.tiles_done as f64/self.tiles_total.max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::Tile;
+
+    #[test]
+    fn test_split()
+    {
+        let tiles: Vec<Tile> = Tile::split(70, 33, 32).collect();
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles[2], Tile {x: 64, y: 0, width: 6, height: 32});
+        assert_eq!(tiles[5], Tile {x: 64, y: 32, width: 6, height: 1});
+
+        let mut covered = vec![0; 70*33];
+        for (x, y) in tiles.iter().flat_map(Tile::pixels)
+        {
+            covered[y*70 + x] += 1;
+        }
+        assert!(covered.into_iter().all(|n| n == 1));
+
+        assert_eq!(Tile::split(0, 10, 8).count(), 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,323 @@
+use num::Float;
+
+use super::Framebuffer;
+
+/// Curves that compress linear HDR values into the displayable range `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMapping<F>
+where
+    F: Float
+{
+    /// Clips everything above `1`.
+    Clamp,
+    /// `x/(1 + x)`.
+    #[default]
+    Reinhard,
+    /// Reinhard's curve, extended so that `white` maps exactly to `1`.
+    ReinhardExtended {
+        white: F
+    },
+    /// Narkowicz's fit of the ACES filmic reference curve.
+    Aces,
+    /// Hable's filmic curve, as used in Uncharted 2, with a linear white point of `11.2`.
+    Filmic
+}
+
+impl<F> ToneMapping<F>
+where
+    F: Float
+{
+    pub fn apply(self, x: F) -> F
+    {
+        let f = |x: f64| F::from(x).unwrap();
+        let x = x.max(F::zero());
+        let y = match self
+        {
+            ToneMapping::Clamp => x,
+            ToneMapping::Reinhard => x/(F::one() + x),
+            ToneMapping::ReinhardExtended {white} => x*(F::one() + x/(white*white))/(F::one() + x),
+            ToneMapping::Aces => (x*(f(2.51)*x + f(0.03)))/(x*(f(2.43)*x + f(0.59)) + f(0.14)),
+            ToneMapping::Filmic => {
+                let hable = |x: F| {
+                    let [a, b, c, d, e, f] = [0.15, 0.50, 0.10, 0.20, 0.02, 0.30].map(f);
+                    (x*(a*x + c*b) + d*e)/(x*(a*x + b) + d*f) - e/f
+                };
+                hable(x)/hable(f(11.2))
+            }
+        };
+        y.min(F::one())
+    }
+}
+
+/// How display values are encoded before quantization.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Transfer<F>
+where
+    F: Float
+{
+    Linear,
+    #[default]
+    Srgb,
+    Gamma(F)
+}
+
+impl<F> Transfer<F>
+where
+    F: Float
+{
+    pub fn encode(self, x: F) -> F
+    {
+        match self
+        {
+            Transfer::Linear => x,
+            Transfer::Srgb => srgb_encode(x),
+            Transfer::Gamma(gamma) => x.max(F::zero()).powf(gamma.recip())
+        }
+    }
+
+    pub fn decode(self, x: F) -> F
+    {
+        match self
+        {
+            Transfer::Linear => x,
+            Transfer::Srgb => srgb_decode(x),
+            Transfer::Gamma(gamma) => x.max(F::zero()).powf(gamma)
+        }
+    }
+}
+
+pub fn srgb_encode<F>(x: F) -> F
+where
+    F: Float
+{
+    let f = |x: f64| F::from(x).unwrap();
+    if x <= f(0.0031308)
+    {
+        x*f(12.92)
+    }
+    else
+    {
+        f(1.055)*x.powf(f(1.0/2.4)) - f(0.055)
+    }
+}
+
+pub fn srgb_decode<F>(x: F) -> F
+where
+    F: Float
+{
+    let f = |x: f64| F::from(x).unwrap();
+    if x <= f(0.04045)
+    {
+        x/f(12.92)
+    }
+    else
+    {
+        ((x + f(0.055))/f(1.055)).powf(f(2.4))
+    }
+}
+
+/// Noise added before quantization to break up banding in smooth gradients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dither
+{
+    #[default]
+    None,
+    /// An 8x8 Bayer matrix.
+    Ordered,
+    /// Deterministic triangular white noise.
+    Noise {
+        seed: u64
+    }
+}
+
+impl Dither
+{
+    /// Offset in units of one quantization step.
+    pub fn offset(self, x: usize, y: usize) -> f64
+    {
+        match self
+        {
+            Dither::None => 0.0,
+            Dither::Ordered => {
+                let (x, y) = (x & 7, y & 7);
+                let xy = x ^ y;
+                // Interleaves the bits of y and x ^ y, most significant first.
+                let i = ((xy & 1) << 5) | ((y & 1) << 4) | ((xy & 2) << 2) | ((y & 2) << 1) | ((xy & 4) >> 1) | ((y & 4) >> 2);
+                (i as f64 + 0.5)/64.0 - 0.5
+            },
+            Dither::Noise {seed} => {
+                let mut h = seed ^ (x as u64).wrapping_mul(0x9e3779b97f4a7c15) ^ (y as u64).wrapping_mul(0xc2b2ae3d27d4eb4f);
+                let mut uniform = || {
+                    h = h.wrapping_add(0x9e3779b97f4a7c15);
+                    let mut z = h;
+                    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+                    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+                    (z ^ (z >> 31)) as f64/u64::MAX as f64
+                };
+                uniform() + uniform() - 1.0
+            }
+        }
+    }
+}
+
+/// Turns linear HDR colors into display-ready 8-bit colors: exposure, then tone mapping, then the transfer function, then dithered quantization.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ToneMapper<F>
+where
+    F: Float
+{
+    /// Exposure adjustment in stops.
+    pub exposure: F,
+    pub tone_mapping: ToneMapping<F>,
+    pub transfer: Transfer<F>,
+    pub dither: Dither
+}
+
+impl<F> ToneMapper<F>
+where
+    F: Float
+{
+    pub fn new(tone_mapping: ToneMapping<F>) -> Self
+    {
+        Self {
+            exposure: F::zero(),
+            tone_mapping,
+            transfer: Transfer::Srgb,
+            dither: Dither::None
+        }
+    }
+
+    pub fn with_exposure(self, exposure: F) -> Self
+    {
+        Self {
+            exposure,
+            ..self
+        }
+    }
+
+    pub fn with_transfer(self, transfer: Transfer<F>) -> Self
+    {
+        Self {
+            transfer,
+            ..self
+        }
+    }
+
+    pub fn with_dither(self, dither: Dither) -> Self
+    {
+        Self {
+            dither,
+            ..self
+        }
+    }
+
+    /// Maps a linear color to encoded display values in `[0, 1]`. NaN is mapped to black.
+    pub fn map(&self, color: [F; 3]) -> [F; 3]
+    {
+        let scale = F::from(2.0).unwrap().powf(self.exposure);
+        color.map(|c| {
+            let c = if c.is_nan() {F::zero()} else {c};
+            self.transfer.encode(self.tone_mapping.apply(c*scale))
+                .max(F::zero())
+                .min(F::one())
+        })
+    }
+
+    pub fn quantize(&self, x: usize, y: usize, color: [F; 3]) -> [u8; 3]
+    {
+        let offset = F::from(self.dither.offset(x, y)).unwrap();
+        let max = F::from(255.0).unwrap();
+        self.map(color).map(|c| (c*max + offset).round().max(F::zero()).min(max).to_u8().unwrap())
+    }
+
+    pub fn apply(&self, framebuffer: &Framebuffer<[F; 3]>) -> Framebuffer<[u8; 3]>
+    {
+        Framebuffer::from_fn(framebuffer.width(), framebuffer.height(), |x, y| {
+            self.quantize(x, y, *framebuffer.get(x, y).unwrap())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::render::Framebuffer;
+
+    use super::{Dither, ToneMapper, ToneMapping, Transfer};
+
+    #[test]
+    fn test_curves()
+    {
+        let curves = [
+            ToneMapping::Clamp,
+            ToneMapping::Reinhard,
+            ToneMapping::ReinhardExtended {white: 4.0},
+            ToneMapping::Aces,
+            ToneMapping::Filmic
+        ];
+        for curve in curves
+        {
+            assert_eq!(curve.apply(0.0f64), 0.0);
+            assert_eq!(curve.apply(-1.0), 0.0);
+            let mut prev = 0.0;
+            for i in 1..1000
+            {
+                let y = curve.apply(i as f64*0.02);
+                assert!(y >= prev && y <= 1.0, "{curve:?} is not monotonic");
+                prev = y;
+            }
+            assert!(curve.apply(1e9) > 0.99);
+        }
+        assert_eq!(ToneMapping::Reinhard.apply(1.0), 0.5);
+        assert!((ToneMapping::ReinhardExtended {white: 4.0}.apply(4.0) - 1.0f64).abs() < 1e-12);
+        assert!((ToneMapping::Filmic.apply(11.2) - 1.0f64).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_srgb()
+    {
+        for i in 0..=100
+        {
+            let x = i as f64/100.0;
+            assert!((super::srgb_decode(super::srgb_encode(x)) - x).abs() < 1e-12);
+        }
+        assert!((super::srgb_encode(0.5f64) - 0.7353569830524495).abs() < 1e-12);
+        assert!((Transfer::Gamma(2.2).decode(Transfer::Gamma(2.2).encode(0.3f64)) - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_dither()
+    {
+        let mut thresholds: Vec<f64> = (0..64).map(|i| Dither::Ordered.offset(i % 8, i / 8)).collect();
+        thresholds.sort_by(f64::total_cmp);
+        for (i, t) in thresholds.into_iter().enumerate()
+        {
+            assert_eq!(t, (i as f64 + 0.5)/64.0 - 0.5);
+        }
+
+        let noise = Dither::Noise {seed: 3};
+        let mean = (0..10000).map(|i| noise.offset(i % 100, i / 100)).sum::<f64>()/10000.0;
+        assert!(mean.abs() < 0.02);
+        assert_eq!(noise.offset(5, 9), noise.offset(5, 9));
+
+        // A value between two levels is rounded to one of them without dithering, but averages out to the right value with it.
+        let framebuffer = Framebuffer::new(64, 64, [100.4/255.0; 3]);
+        let tone_mapper = ToneMapper::new(ToneMapping::Clamp)
+            .with_transfer(Transfer::Linear);
+        for (dither, expected) in [(Dither::None, 100.0), (Dither::Ordered, 100.4), (noise, 100.4)]
+        {
+            let image = tone_mapper.with_dither(dither).apply(&framebuffer);
+            let mean = image.pixels().iter().map(|p| p[0] as f64).sum::<f64>()/image.pixels().len() as f64;
+            assert!((mean - expected).abs() < 0.05, "{dither:?}: {mean}");
+        }
+    }
+
+    #[test]
+    fn test_tone_mapper()
+    {
+        let tone_mapper = ToneMapper::new(ToneMapping::Reinhard);
+        assert_eq!(tone_mapper.quantize(0, 0, [0.0, f64::NAN, f64::INFINITY]), [0, 0, 255]);
+        assert_eq!(tone_mapper.map([1.0f64; 3]), [super::srgb_encode(0.5); 3]);
+        assert_eq!(tone_mapper.with_exposure(1.0).map([0.5f64; 3]), tone_mapper.map([1.0; 3]));
+    }
+}
\ No newline at end of file
@@ -2,6 +2,9 @@ moddef::moddef!(
     flat(pub) mod {
         circle,
         line,
-        rectangle
+        polygon,
+        polyline,
+        rectangle,
+        segment
     }
 );
\ No newline at end of file
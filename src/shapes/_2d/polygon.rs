@@ -0,0 +1,65 @@
+use core::{iter::Sum, ops::{AddAssign, DivAssign}};
+
+use num::Float;
+
+use crate::{shapes::Shape, Ray, Raytrace, RaytraceWithNorm};
+
+use super::{polyline, Segment};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Polygon<F, const N: usize>
+where
+    F: Float
+{
+    pub vertices: [[F; 2]; N]
+}
+
+impl<F, const N: usize> Polygon<F, N>
+where
+    F: Float
+{
+    pub fn new(vertices: [[F; 2]; N]) -> Self
+    {
+        Self {
+            vertices
+        }
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment<F>> + '_
+    {
+        (0..N).map(|i| Segment::new(self.vertices[i], self.vertices[(i + 1) % N]))
+    }
+
+    pub fn contains(&self, x: [F; 2]) -> bool
+    {
+        let [x, y] = x;
+
+        let mut inside = false;
+        for Segment {r1: [x1, y1], r2: [x2, y2]} in self.segments()
+        {
+            if (y1 > y) != (y2 > y) && x < (x2 - x1)*(y - y1)/(y2 - y1) + x1
+            {
+                inside = !inside
+            }
+        }
+        inside
+    }
+}
+
+impl<F, const N: usize> Shape<F, 2> for Polygon<F, N>
+where
+    F: Float + AddAssign + DivAssign + Sum
+{
+    fn raytrace(&self, ray: &Ray<F, 2>) -> Raytrace<F, 2>
+    {
+        self.segments()
+            .map(|segment| segment.raytrace(ray))
+            .reduce(Raytrace::min)
+            .unwrap_or_else(Raytrace::miss)
+    }
+
+    fn raytrace_with_norm(&self, ray: &Ray<F, 2>) -> RaytraceWithNorm<F, 2>
+    {
+        polyline::_raytrace_with_norm(self.segments(), ray)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,68 @@
+use core::{iter::Sum, ops::{AddAssign, DivAssign}};
+
+use num::Float;
+
+use crate::{shapes::Shape, Ray, Raytrace, RaytraceWithNorm};
+
+use super::Segment;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Polyline<F, const N: usize>
+where
+    F: Float
+{
+    pub vertices: [[F; 2]; N]
+}
+
+impl<F, const N: usize> Polyline<F, N>
+where
+    F: Float
+{
+    pub fn new(vertices: [[F; 2]; N]) -> Self
+    {
+        Self {
+            vertices
+        }
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment<F>> + '_
+    {
+        self.vertices.windows(2)
+            .map(|r| Segment::new(r[0], r[1]))
+    }
+}
+
+impl<F, const N: usize> Shape<F, 2> for Polyline<F, N>
+where
+    F: Float + AddAssign + DivAssign + Sum
+{
+    fn raytrace(&self, ray: &Ray<F, 2>) -> Raytrace<F, 2>
+    {
+        self.segments()
+            .map(|segment| segment.raytrace(ray))
+            .reduce(Raytrace::min)
+            .unwrap_or_else(Raytrace::miss)
+    }
+
+    fn raytrace_with_norm(&self, ray: &Ray<F, 2>) -> RaytraceWithNorm<F, 2>
+    {
+        _raytrace_with_norm(self.segments(), ray)
+    }
+}
+
+/// Traces a chain of segments, offsetting `u` by the index of the segment that was hit so that it runs continuously along the whole chain.
+pub(super) fn _raytrace_with_norm<F>(segments: impl Iterator<Item = Segment<F>>, ray: &Ray<F, 2>) -> RaytraceWithNorm<F, 2>
+where
+    F: Float + AddAssign + DivAssign + Sum
+{
+    segments.enumerate()
+        .map(|(i, segment)| {
+            let raytrace = segment.raytrace_with_norm(ray);
+            RaytraceWithNorm {
+                uv: raytrace.uv.map(|[u, v]| [u + F::from(i).unwrap(), v]),
+                ..raytrace
+            }
+        })
+        .reduce(RaytraceWithNorm::min)
+        .unwrap_or_else(RaytraceWithNorm::miss)
+}
\ No newline at end of file
@@ -0,0 +1,79 @@
+use core::{iter::Sum, ops::{AddAssign, DivAssign}};
+
+use num::Float;
+
+use crate::{shapes::Shape, vec2, Ray, Raytrace, RaytraceWithNorm};
+
+use super::Line;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Segment<F>
+where
+    F: Float
+{
+    pub r1: [F; 2],
+    pub r2: [F; 2]
+}
+
+impl<F> Segment<F>
+where
+    F: Float
+{
+    pub fn new(r1: [F; 2], r2: [F; 2]) -> Self
+    {
+        Self {
+            r1,
+            r2
+        }
+    }
+
+    pub fn line(&self) -> Line<F>
+    {
+        Line {
+            r: self.r1,
+            n: vec2::perpendicular(vec2::sub(self.r2, self.r1))
+        }
+    }
+
+    /// Position of `x` along the segment, from zero at `r1` to one at `r2`.
+    pub fn uv(&self, x: [F; 2]) -> [F; 2]
+    {
+        let d = vec2::sub(self.r2, self.r1);
+        [vec2::mul_dot(vec2::sub(x, self.r1), d)/vec2::magnitude_squared(d), F::zero()]
+    }
+
+    fn _raytrace(&self, ray: &Ray<F, 2>) -> Option<(Raytrace<F, 2>, F)>
+    where
+        F: Sum
+    {
+        let d = vec2::sub(self.r2, self.r1);
+        let d2 = vec2::magnitude_squared(d);
+        self.line()
+            ._raytrace(ray)
+            .filter(|(Raytrace {t}, _)| {
+                let s = vec2::mul_dot(vec2::sub(ray.propagate(*t), self.r1), d);
+                s >= F::zero() && s <= d2
+            })
+    }
+}
+
+impl<F> Shape<F, 2> for Segment<F>
+where
+    F: Float + AddAssign + DivAssign + Sum
+{
+    fn raytrace(&self, ray: &Ray<F, 2>) -> Raytrace<F, 2>
+    {
+        self._raytrace(ray)
+            .map(|(ray, _)| ray)
+            .unwrap_or_else(Raytrace::miss)
+    }
+
+    fn raytrace_with_norm(&self, ray: &Ray<F, 2>) -> RaytraceWithNorm<F, 2>
+    {
+        self._raytrace(ray)
+            .map(|(raytrace, vn)| raytrace.with_norm(|| self.line()._norm(vn))
+                .with_uv(|| self.uv(ray.propagate(raytrace.t)))
+                .with_tangents(|| [vec2::normalize(vec2::sub(self.r2, self.r1)), [F::zero(); 2]])
+            ).unwrap_or_else(RaytraceWithNorm::miss)
+    }
+}
\ No newline at end of file
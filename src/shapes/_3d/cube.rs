@@ -12,7 +12,6 @@ mod test
     use super::Cube;
 
     #[test]
-    #[ignore]
     fn test()
     {
         let shape = Transform::new(Cube {
@@ -25,6 +24,6 @@ mod test
         const D: f64 = 0.0;
         const A: f64 = 0.1;
 
-        tests::project_3d_spin(&shape, [0.0, 0.0, -20.0], D, A);
+        tests::assert_golden("cube", &shape, [0.0, 0.0, -20.0], D, A);
     }
 }
\ No newline at end of file
@@ -0,0 +1,103 @@
+use core::{iter::Sum, ops::{AddAssign, DivAssign}};
+
+use num::Float;
+
+use crate::{shapes::{nd::HyperPlane, Shape}, vec3, Ray, Raytrace, RaytraceWithNorm};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Disk<F>
+where
+    F: Float
+{
+    pub r0: [F; 3],
+    pub n: [F; 3],
+    pub r: F
+}
+
+impl<F> Disk<F>
+where
+    F: Float
+{
+    pub fn new(r0: [F; 3], n: [F; 3], r: F) -> Self
+    {
+        Self {
+            r0,
+            n,
+            r
+        }
+    }
+
+    pub fn plane(&self) -> HyperPlane<F, 3>
+    {
+        HyperPlane {
+            r: self.r0,
+            n: self.n
+        }
+    }
+
+    /// Planar mapping of the bounding square of the disk to the unit square.
+    pub fn uv(&self, x: [F; 3]) -> [F; 2]
+    where
+        F: Sum
+    {
+        let half = F::from(0.5).unwrap();
+        self.plane()
+            .uv(x)
+            .map(|uv| half + uv*half/self.r)
+    }
+
+    fn _raytrace(&self, ray: &Ray<F, 3>) -> Option<(Raytrace<F, 3>, F)>
+    where
+        F: Sum
+    {
+        self.plane()
+            ._raytrace(ray)
+            .filter(|(Raytrace {t}, _)| {
+                let d = vec3::sub(ray.propagate(*t), self.r0);
+                vec3::magnitude_squared(d) <= self.r*self.r
+            })
+    }
+}
+
+impl<F> Shape<F, 3> for Disk<F>
+where
+    F: Float + AddAssign + DivAssign + Sum
+{
+    fn raytrace(&self, ray: &Ray<F, 3>) -> Raytrace<F, 3>
+    {
+        self._raytrace(ray)
+            .map(|(ray, _)| ray)
+            .unwrap_or_else(Raytrace::miss)
+    }
+
+    fn raytrace_with_norm(&self, ray: &Ray<F, 3>) -> RaytraceWithNorm<F, 3>
+    {
+        self._raytrace(ray)
+            .map(|(raytrace, vn)| raytrace.with_norm(|| self.plane()._norm(vn))
+                .with_uv(|| self.uv(ray.propagate(raytrace.t)))
+                .with_tangents(|| self.plane().basis())
+            ).unwrap_or_else(RaytraceWithNorm::miss)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::FRAC_PI_4;
+
+    use crate::{shapes::Transform, tests};
+
+    use super::Disk;
+
+    #[test]
+    fn test()
+    {
+        let shape = Transform::new(Disk::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], 1.0))
+            .rotate([1.0, 0.0, 0.0], -FRAC_PI_4);
+
+        const D: f64 = 2.0;
+        const A: f64 = 0.0;
+
+        tests::assert_golden("disk", &shape, [0.0, 0.0, -3.0], D, A);
+    }
+}
\ No newline at end of file
@@ -1,7 +1,10 @@
 moddef::moddef!(
     flat(pub) mod {
+        annulus,
         cube,
+        disk,
         plane,
+        polygon,
         rectangular_prism,
         sphere,
         tetrahedron,
@@ -10,7 +10,6 @@ mod test
     use super::Plane;
 
     #[test]
-    #[ignore]
     fn test()
     {
         let shape = Plane {
@@ -21,6 +20,6 @@ mod test
         const D: f64 = 1.0;
         const A: f64 = 0.1;
 
-        tests::project_3d_spin(&shape, [0.0, 0.0, -1.0], D, A);
+        tests::assert_golden("plane", &shape, [0.0, 0.0, -1.0], D, A);
     }
 }
\ No newline at end of file
@@ -0,0 +1,166 @@
+use core::{iter::Sum, ops::{AddAssign, DivAssign}};
+
+use num::Float;
+
+use crate::{shapes::{nd::HyperPlane, Shape}, vec3, Ray, Raytrace, RaytraceWithNorm};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Polygon<F, const N: usize>
+where
+    F: Float
+{
+    vertices: [[F; 3]; N],
+    plane: HyperPlane<F, 3>,
+    basis: [[F; 3]; 2],
+    uv_min: [F; 2],
+    uv_scale: [F; 2]
+}
+
+impl<F, const N: usize> Polygon<F, N>
+where
+    F: Float
+{
+    pub fn new(vertices: [[F; 3]; N]) -> Self
+    where
+        F: Sum
+    {
+        // Newell's method, so that concave and slightly non-planar polygons still get a sensible normal
+        let mut n = [F::zero(); 3];
+        for i in 0..N
+        {
+            let [x1, y1, z1] = vertices[i];
+            let [x2, y2, z2] = vertices[(i + 1) % N];
+            n[0] = n[0] + (y1 - y2)*(z1 + z2);
+            n[1] = n[1] + (z1 - z2)*(x1 + x2);
+            n[2] = n[2] + (x1 - x2)*(y1 + y2);
+        }
+        let plane = HyperPlane {
+            r: vertices.first()
+                .copied()
+                .unwrap_or([F::zero(); 3]),
+            n
+        };
+
+        // The bounding box of the vertices within the plane, which is mapped to the unit square
+        let mut uv_min = [F::infinity(); 2];
+        let mut uv_max = [F::neg_infinity(); 2];
+        for x in vertices
+        {
+            for ((uv, min), max) in plane.uv(x)
+                .into_iter()
+                .zip(uv_min.iter_mut())
+                .zip(uv_max.iter_mut())
+            {
+                *min = min.min(uv);
+                *max = max.max(uv);
+            }
+        }
+        let uv_scale = core::array::from_fn(|i| {
+            let size = uv_max[i] - uv_min[i];
+            if size > F::zero() {size.recip()} else {F::zero()}
+        });
+
+        Self {
+            vertices,
+            plane,
+            basis: plane.basis(),
+            uv_min,
+            uv_scale
+        }
+    }
+
+    pub fn vertices(&self) -> &[[F; 3]; N]
+    {
+        &self.vertices
+    }
+
+    pub fn plane(&self) -> HyperPlane<F, 3>
+    {
+        self.plane
+    }
+
+    /// Planar mapping of the bounding box of the polygon within its plane to the unit square.
+    pub fn uv(&self, x: [F; 3]) -> [F; 2]
+    {
+        let d = vec3::sub(x, self.plane.r);
+        core::array::from_fn(|i| (vec3::mul_dot(d, self.basis[i]) - self.uv_min[i])*self.uv_scale[i])
+    }
+
+    fn contains(&self, x: [F; 3], n: [F; 3]) -> bool
+    {
+        // Even-odd rule in the coordinate plane where the polygon has the largest projected area
+        let k = (0..3).reduce(|a, b| if n[b].abs() > n[a].abs() {b} else {a})
+            .unwrap_or(2);
+        let (u, v) = ((k + 1) % 3, (k + 2) % 3);
+
+        let mut inside = false;
+        for i in 0..N
+        {
+            let a = self.vertices[i];
+            let b = self.vertices[(i + 1) % N];
+            if (a[v] > x[v]) != (b[v] > x[v])
+                && x[u] < (b[u] - a[u])*(x[v] - a[v])/(b[v] - a[v]) + a[u]
+            {
+                inside = !inside
+            }
+        }
+        inside
+    }
+
+    fn _raytrace(&self, ray: &Ray<F, 3>) -> Option<(Raytrace<F, 3>, F)>
+    where
+        F: Sum
+    {
+        self.plane._raytrace(ray)
+            .filter(|(Raytrace {t}, _)| self.contains(ray.propagate(*t), self.plane.n))
+    }
+}
+
+impl<F, const N: usize> Shape<F, 3> for Polygon<F, N>
+where
+    F: Float + AddAssign + DivAssign + Sum
+{
+    fn raytrace(&self, ray: &Ray<F, 3>) -> Raytrace<F, 3>
+    {
+        self._raytrace(ray)
+            .map(|(ray, _)| ray)
+            .unwrap_or_else(Raytrace::miss)
+    }
+
+    fn raytrace_with_norm(&self, ray: &Ray<F, 3>) -> RaytraceWithNorm<F, 3>
+    {
+        self._raytrace(ray)
+            .map(|(raytrace, vn)| raytrace.with_norm(|| self.plane._norm(vn))
+                .with_uv(|| self.uv(ray.propagate(raytrace.t)))
+                .with_tangents(|| self.basis)
+            ).unwrap_or_else(RaytraceWithNorm::miss)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::FRAC_PI_4;
+
+    use crate::{shapes::Transform, tests};
+
+    use super::Polygon;
+
+    #[test]
+    fn test()
+    {
+        // A concave arrow head
+        let shape = Transform::new(Polygon::new([
+                [-1.0, -1.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [1.0, -1.0, 0.0],
+                [0.0, -0.25, 0.0]
+            ]))
+            .rotate([1.0, 0.0, 0.0], -FRAC_PI_4);
+
+        const D: f64 = 2.0;
+        const A: f64 = 0.0;
+
+        tests::assert_golden("polygon", &shape, [0.0, 0.0, -3.0], D, A);
+    }
+}
\ No newline at end of file
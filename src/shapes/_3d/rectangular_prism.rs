@@ -12,7 +12,6 @@ mod test
     use super::RectangularPrism;
 
     #[test]
-    #[ignore]
     fn test()
     {
         let shape = Transform::new(RectangularPrism {
@@ -24,6 +23,6 @@ mod test
         const D: f64 = 3.0;
         const A: f64 = 0.0;
 
-        tests::project_3d_spin(&shape, [0.0, 0.0, -3.0], D, A);
+        tests::assert_golden("rectangular_prism", &shape, [0.0, 0.0, -3.0], D, A);
     }
 }
\ No newline at end of file
@@ -5,12 +5,11 @@ pub type Sphere<F> = HyperSphere<F, 3>;
 #[cfg(test)]
 mod test
 {
-    use crate::{shapes::Transform, tests};
+    use crate::{shapes::{Shape, Transform}, tests, vec3, Ray};
 
     use super::Sphere;
 
     #[test]
-    #[ignore]
     fn test()
     {
         let shape = Transform::new(Sphere::new([0.0, 0.0, 0.0], 1.0))
@@ -19,6 +18,28 @@ mod test
         const D: f64 = 3.0;
         const A: f64 = 0.0;
 
-        tests::project_3d_spin(&shape, [0.0, 0.0, -5.0], D, A);
+        tests::assert_golden("sphere", &shape, [0.0, 0.0, -5.0], D, A);
+    }
+
+    #[test]
+    fn test_scaled_norm()
+    {
+        let shape = Transform::new(Sphere::new([0.0, 0.0, 0.0], 1.0))
+            .scale([2.0, 1.0, 1.0]);
+
+        // The ellipsoid x²/4 + y² + z² = 1 has the gradient [x/2, 2y, 2z]
+        let [x, y, z] = [1.0f64, 0.5, -0.5f64.sqrt()];
+        let ray = Ray {
+            r: [x, y, -5.0],
+            v: [0.0, 0.0, 1.0]
+        };
+
+        let raytrace = shape.raytrace_with_norm(&ray);
+        assert!((raytrace.t - (5.0 + z)).abs() < 1e-12);
+
+        let n = raytrace.n.unwrap();
+        let n_exp = vec3::normalize([x/2.0, 2.0*y, 2.0*z]);
+        assert!((vec3::magnitude(n) - 1.0).abs() < 1e-12);
+        assert!(n.into_iter().zip(n_exp).all(|(n, n_exp)| (n - n_exp).abs() < 1e-12), "{n:?} != {n_exp:?}");
     }
 }
\ No newline at end of file
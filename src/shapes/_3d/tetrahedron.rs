@@ -2,7 +2,7 @@ use core::{iter::Sum, ops::{AddAssign, DivAssign, MulAssign}};
 
 use num::Float;
 
-use crate::{shapes::{Shape, _3d::Plane, nd::HyperPlane}, Ray, Raytrace, RaytraceWithNorm};
+use crate::{shapes::{Shape, _3d::Plane, nd::HyperPlane}, vec3, Ray, Raytrace, RaytraceWithNorm};
 
 #[derive(Debug, Clone)]
 pub struct Tetrahedron<F>
@@ -17,24 +17,68 @@ impl<F> Tetrahedron<F>
 where
     F: Float
 {
+    pub fn vertices(&self) -> [[F; 3]; 4]
+    {
+        unsafe {
+            V.into_iter()
+                .zip(self.diameters)
+                .map(|(v, d)| {
+                    let mut r = self.center;
+                    for (r, v) in r.iter_mut()
+                        .zip(v)
+                    {
+                        *r = *r + d*F::from(v/2.0).unwrap()
+                    }
+                    r
+                })
+                .next_chunk::<4>()
+                .unwrap_unchecked()
+        }
+    }
+
+    /// Barycentric coordinates of `x` on face `i`, as the weights of its second and third vertex.
+    pub fn uv(&self, i: usize, x: [F; 3]) -> [F; 2]
+    {
+        let v = self.vertices();
+        let a = v[i % 4];
+        let e1 = vec3::sub(v[(i + 1) % 4], a);
+        let e2 = vec3::sub(v[(i + 2) % 4], a);
+        let ex = vec3::sub(x, a);
+
+        let d11 = vec3::mul_dot(e1, e1);
+        let d12 = vec3::mul_dot(e1, e2);
+        let d22 = vec3::mul_dot(e2, e2);
+        let dx1 = vec3::mul_dot(ex, e1);
+        let dx2 = vec3::mul_dot(ex, e2);
+        let det = d11*d22 - d12*d12;
+
+        [(d22*dx1 - d12*dx2)/det, (d11*dx2 - d12*dx1)/det]
+    }
+
+    /// Orthonormal frame of face `i`, with the tangent along its first edge.
+    pub fn tangents(&self, i: usize) -> [[F; 3]; 2]
+    {
+        let v = self.vertices();
+        let a = v[i % 4];
+        let t = vec3::normalize(vec3::sub(v[(i + 1) % 4], a));
+        let e2 = vec3::sub(v[(i + 2) % 4], a);
+        let e2t = vec3::mul_dot(e2, t);
+        let b = vec3::normalize(vec3::sub(e2, t.map(|t| t*e2t)));
+        [t, b]
+    }
+
     fn _raytrace<I, N>(
         &self,
         ray: &Ray<F, 3>,
         inside: impl FnOnce(&[[F; 3]; 4], &[HyperPlane<F, 3>; 4]) -> I,
         n_init: N,
-        n: impl Fn(HyperPlane<F, 3>, I) -> N
+        n: impl Fn(usize, HyperPlane<F, 3>, I) -> N
     ) -> (Raytrace<F, 3>, N)
     where
         F: MulAssign + Sum,
         I: Copy
     {
-        let v = unsafe {
-            V.into_iter()
-                .zip(self.diameters)
-                .map(|(v, d)| v.map(|v| d*F::from(v/2.0).unwrap()))
-                .next_chunk::<4>()
-                .unwrap_unchecked()
-        };
+        let v = self.vertices();
 
         let s = core::array::from_fn::<_, 4, _>(|i| {
             let mut v = v;
@@ -66,7 +110,7 @@ where
                     .sum::<F>()/vn
             };
 
-            if t < F::zero() || t >= t_min
+            if t < F::zero() || t >= t_min || t.is_nan()
             {
                 continue;
             }
@@ -96,7 +140,7 @@ where
             }
 
             t_min = t;
-            n_min = n(si, inside);
+            n_min = n(i, si, inside);
         }
 
         (
@@ -121,13 +165,13 @@ where
 {
     fn raytrace(&self, ray: &Ray<F, 3>) -> Raytrace<F, 3>
     {
-        let (raytrace, ()) = self._raytrace(ray, |_, _| (), (), |_, _| ());
+        let (raytrace, ()) = self._raytrace(ray, |_, _| (), (), |_, _, _| ());
         raytrace
     }
 
     fn raytrace_with_norm(&self, ray: &Ray<F, 3>) -> RaytraceWithNorm<F, 3>
     {
-        let (raytrace, n) = self._raytrace(
+        let (raytrace, hit) = self._raytrace(
             ray,
             |v, s| {
                 for k in 0..4
@@ -155,7 +199,7 @@ where
                 true
             },
             None,
-            |si, inside| {
+            |i, si, inside| {
                 let mut n = si.n;
                 let mut n_norm = si.n.into_iter()
                     .map(|n| n*n)
@@ -170,13 +214,19 @@ where
                     *n /= n_norm
                 }
 
-                Some(n)
+                Some((i, n))
             }
         );
 
-        RaytraceWithNorm {
-            raytrace,
-            n
+        match hit
+        {
+            Some((i, n)) => RaytraceWithNorm {
+                raytrace,
+                n: Some(n),
+                ..RaytraceWithNorm::miss()
+            }.with_uv(|| self.uv(i, ray.propagate(raytrace.t)))
+                .with_tangents(|| self.tangents(i)),
+            None => RaytraceWithNorm::miss()
         }
     }
 }
@@ -191,7 +241,6 @@ mod test
     use super::Tetrahedron;
 
     #[test]
-    #[ignore]
     fn test()
     {
         let shape = Transform::new(Tetrahedron {
@@ -203,6 +252,6 @@ mod test
         const D: f64 = 1.0;
         const A: f64 = 0.0;
 
-        tests::project_3d_spin(&shape, [0.0, 0.0, -3.0], D, A);
+        tests::assert_golden("tetrahedron", &shape, [0.0, 0.0, -3.0], D, A);
     }
 }
\ No newline at end of file
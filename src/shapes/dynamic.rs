@@ -0,0 +1,137 @@
+use alloc::boxed::Box;
+
+use num::Float;
+
+use crate::{PreparedRay, Ray, RayBatch, Raytrace, RaytraceBatch, RaytraceWithId, RaytraceWithNorm};
+
+use super::Shape;
+
+// Fails to compile if `Shape` stops being dyn-compatible
+const _: Option<&dyn Shape<f64, 3>> = None;
+
+// Only `Box` and references are fundamental, so other pointers such as `Arc` would overlap with the collection impl and must go through `Shared` instead
+macro_rules! impl_dyn_shape {
+    ($($dyn:tt)*) => {
+        impl<'a, F, const D: usize> Shape<F, D> for Box<$($dyn)* + 'a>
+        where
+            F: Float
+        {
+            fn raytrace(&self, ray: &Ray<F, D>) -> Raytrace<F, D>
+            {
+                (**self).raytrace(ray)
+            }
+
+            fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>
+            {
+                (**self).raytrace_with_norm(ray)
+            }
+
+            fn raytrace_with_id(&self, ray: &Ray<F, D>) -> RaytraceWithId<F, D>
+            {
+                (**self).raytrace_with_id(ray)
+            }
+
+            fn raytrace_prepared(&self, ray: &PreparedRay<F, D>) -> Raytrace<F, D>
+            {
+                (**self).raytrace_prepared(ray)
+            }
+
+            fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+            {
+                (**self).raytrace_batch(rays)
+            }
+        }
+    };
+}
+
+impl_dyn_shape!(dyn Shape<F, D>);
+impl_dyn_shape!(dyn Shape<F, D> + Send);
+impl_dyn_shape!(dyn Shape<F, D> + Sync);
+impl_dyn_shape!(dyn Shape<F, D> + Send + Sync);
+
+macro_rules! impl_dyn_shape_ref {
+    ($($dyn:tt)*) => {
+        impl<'a, F, const D: usize> Shape<F, D> for &'a ($($dyn)* + 'a)
+        where
+            F: Float
+        {
+            fn raytrace(&self, ray: &Ray<F, D>) -> Raytrace<F, D>
+            {
+                (**self).raytrace(ray)
+            }
+
+            fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>
+            {
+                (**self).raytrace_with_norm(ray)
+            }
+
+            fn raytrace_with_id(&self, ray: &Ray<F, D>) -> RaytraceWithId<F, D>
+            {
+                (**self).raytrace_with_id(ray)
+            }
+
+            fn raytrace_prepared(&self, ray: &PreparedRay<F, D>) -> Raytrace<F, D>
+            {
+                (**self).raytrace_prepared(ray)
+            }
+
+            fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+            {
+                (**self).raytrace_batch(rays)
+            }
+        }
+    };
+}
+
+impl_dyn_shape_ref!(dyn Shape<F, D>);
+impl_dyn_shape_ref!(dyn Shape<F, D> + Send);
+impl_dyn_shape_ref!(dyn Shape<F, D> + Sync);
+impl_dyn_shape_ref!(dyn Shape<F, D> + Send + Sync);
+
+#[cfg(test)]
+mod test
+{
+    use std::{sync::Arc, thread};
+
+    use crate::{shapes::{Shape, Shared, Transform, _3d::{Cube, Plane, Sphere, Tetrahedron}}, Ray};
+
+    #[test]
+    fn test()
+    {
+        let shapes: Vec<Box<dyn Shape<f64, 3> + Send + Sync>> = vec![
+            Box::new(Sphere::new([-2.0, 0.0, 0.0], 1.0)),
+            Box::new(Transform::new(Cube {center: [2.0, 0.0, 0.0], radius: 1.0}).rotate([0.0, 1.0, 0.0], 0.5)),
+            Box::new(Tetrahedron {center: [0.0, 2.0, 0.0], diameters: [2.0, 2.0, 2.0, 2.0]}),
+            Box::new(Plane {r: [0.0, 0.0, 10.0], n: [0.0, 0.0, 1.0]})
+        ];
+
+        let ray = |x, y| Ray {
+            r: [x, y, -5.0],
+            v: [0.0, 0.0, 1.0]
+        };
+        for (x, y) in [(-2.0, 0.0), (2.0, 0.0), (0.0, 2.0)]
+        {
+            let raytrace = shapes.raytrace_with_norm(&ray(x, y));
+            assert!(raytrace.is_hit() && raytrace.t < 10.0);
+        }
+        assert!((shapes.raytrace(&ray(0.0, -3.0)).t - 15.0).abs() < 1e-12);
+
+        let refs: Vec<&dyn Shape<f64, 3>> = shapes.iter().map(|shape| &**shape as &dyn Shape<f64, 3>).collect();
+        assert_eq!(refs.raytrace(&ray(-2.0, 0.0)), shapes.raytrace(&ray(-2.0, 0.0)));
+        let refs: Vec<&(dyn Shape<f64, 3> + Send)> = shapes.iter().map(|shape| &**shape as &(dyn Shape<f64, 3> + Send)).collect();
+        assert_eq!(refs.raytrace(&ray(-2.0, 0.0)), shapes.raytrace(&ray(-2.0, 0.0)));
+
+        let shared: Vec<Shared<Arc<dyn Shape<f64, 3>>>> = vec![
+            Shared(Arc::new(Sphere::new([-2.0, 0.0, 0.0], 1.0))),
+            Shared(Arc::new(Plane {r: [0.0, 0.0, 10.0], n: [0.0, 0.0, 1.0]}))
+        ];
+        assert_eq!(shared.raytrace(&ray(-2.0, 0.0)), shapes.raytrace(&ray(-2.0, 0.0)));
+
+        let shapes = Arc::new(shapes);
+        let handle = {
+            let shapes = shapes.clone();
+            thread::spawn(move || shapes.raytrace(&ray(2.0, 0.0)))
+        };
+        assert_eq!(handle.join().unwrap(), shapes.raytrace(&ray(2.0, 0.0)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,98 @@
+use core::ops::Deref;
+
+use num::Float;
+
+use crate::{PreparedRay, Ray, RayBatch, Raytrace, RaytraceBatch, RaytraceWithId, RaytraceWithNorm};
+
+use super::{Shape, Transform};
+
+/// A shape behind a pointer, such as `Arc<S>` or `&S`, so that many placements can share the same geometry.
+#[derive(Debug, Clone, Copy)]
+pub struct Shared<R>(pub R);
+
+pub type Instance<F, R, const D: usize> = Transform<F, Shared<R>, D>;
+
+impl<R> Shared<R>
+{
+    pub fn instance<F, const D: usize>(&self) -> Instance<F, R, D>
+    where
+        F: Float,
+        R: Deref<Target: Shape<F, D>> + Clone
+    {
+        Transform::new(self.clone())
+    }
+}
+
+impl<F, R, const D: usize> Shape<F, D> for Shared<R>
+where
+    F: Float,
+    R: Deref<Target: Shape<F, D>>
+{
+    fn raytrace(&self, ray: &Ray<F, D>) -> Raytrace<F, D>
+    {
+        self.0.raytrace(ray)
+    }
+
+    fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>
+    {
+        self.0.raytrace_with_norm(ray)
+    }
+
+    fn raytrace_with_id(&self, ray: &Ray<F, D>) -> RaytraceWithId<F, D>
+    {
+        self.0.raytrace_with_id(ray)
+    }
+
+    fn raytrace_prepared(&self, ray: &PreparedRay<F, D>) -> Raytrace<F, D>
+    {
+        self.0.raytrace_prepared(ray)
+    }
+
+    fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+    {
+        self.0.raytrace_batch(rays)
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use std::sync::Arc;
+
+    use crate::{shapes::{Shape, _3d::Tetrahedron}, Ray};
+
+    use super::{Instance, Shared};
+
+    #[test]
+    fn test()
+    {
+        let tree = Shared(Arc::new(Tetrahedron {
+            center: [0.0, 0.0, 0.0],
+            diameters: [1.0, 1.0, 1.0, 1.0]
+        }));
+
+        let forest: Vec<Instance<f64, _, 3>> = (0..10)
+            .map(|i| tree.instance()
+                .scale([1.0, 1.0 + i as f64*0.1, 1.0])
+                .translate([i as f64*3.0, 0.0, 0.0])
+            )
+            .collect();
+        assert_eq!(Arc::strong_count(&tree.0), 11);
+
+        for i in 0..10
+        {
+            let ray = Ray {
+                r: [i as f64*3.0, 0.0, -5.0],
+                v: [0.0, 0.0, 1.0]
+            };
+            let raytrace = forest.raytrace_with_norm(&ray);
+            let t = tree.instance().raytrace(&Ray {
+                r: [0.0, 0.0, -5.0],
+                v: [0.0, 0.0, 1.0]
+            }).t;
+            assert!(raytrace.is_hit());
+            assert!((raytrace.t - t).abs() < 1e-12);
+        }
+        assert!(forest.raytrace(&Ray {r: [1.5, 0.0, -5.0], v: [0.0, 0.0, 1.0]}).is_miss());
+    }
+}
\ No newline at end of file
@@ -1,11 +1,16 @@
 moddef::moddef!(
     flat(pub) mod {
+        instance,
+        scene,
         transform
     },
     pub mod {
         _2d,
         _3d,
         nd
+    },
+    mod {
+        dynamic
     }
 );
 
@@ -13,8 +18,14 @@ use core::ops::Deref;
 
 use num::Float;
 
-use crate::{Ray, Raytrace, RaytraceWithNorm};
+use crate::{PacketFloat, PreparedRay, Ray, RayBatch, RayPacket, Raytrace, RaytraceBatch, RaytraceWithId, RaytraceWithNorm};
 
+/// A surface that can be intersected by rays.
+///
+/// The `t` of a hit is always the ray parameter, so the point of intersection is `ray.propagate(t)` regardless of the length of `ray.v`.
+///
+/// The trait is kept dyn-compatible, so mixed shapes can be stored as `Box<dyn Shape<F, D> + Send + Sync>`, `&dyn Shape<F, D>` or `Shared<Arc<dyn Shape<F, D>>>`, all
+/// of which implement `Shape` themselves and thereby also work inside collections.
 pub trait Shape<F, const D: usize>
 where
     F: Float
@@ -22,6 +33,49 @@ where
     fn raytrace(&self, ray: &Ray<F, D>) -> Raytrace<F, D>;
 
     fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>;
+
+    /// Like [`raytrace_with_norm`](Self::raytrace_with_norm), but also tells which object of a [`Scene`] was hit. Shapes outside of a scene give no ID.
+    fn raytrace_with_id(&self, ray: &Ray<F, D>) -> RaytraceWithId<F, D>
+    {
+        RaytraceWithId {
+            raytrace: self.raytrace_with_norm(ray),
+            id: None
+        }
+    }
+
+    /// Like [`raytrace`](Self::raytrace), but with a [`PreparedRay`] that can be shared by many shapes.
+    ///
+    /// Boxes override this with a slab test that multiplies by the cached reciprocal direction instead of dividing, so their `t` may differ from
+    /// [`raytrace`](Self::raytrace) in the last bits. Collections pass the prepared ray on to each of their shapes.
+    fn raytrace_prepared(&self, ray: &PreparedRay<F, D>) -> Raytrace<F, D>
+    {
+        self.raytrace(ray.ray())
+    }
+
+    /// Traces a whole batch of rays, giving the same `t` and normal as [`raytrace_with_norm`](Self::raytrace_with_norm) for each of them.
+    ///
+    /// By default the rays are traced one by one. Primitives override this to skip work that is only needed for texture coordinates and tangents, and
+    /// collections trace one shape at a time over the whole batch.
+    fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+    {
+        let mut batch = RaytraceBatch::miss(rays.len());
+        for (i, ray) in rays.rays().enumerate()
+        {
+            batch.set(i, &self.raytrace_with_norm(&ray));
+        }
+        batch
+    }
+
+    /// Traces `N` rays at once, with exactly the same results as calling [`raytrace`](Self::raytrace) on each of them.
+    ///
+    /// By default the rays are simply traced one by one. Primitives override this with SIMD implementations.
+    fn raytrace_packet<const N: usize>(&self, packet: &RayPacket<F, D, N>) -> [Raytrace<F, D>; N]
+    where
+        Self: Sized,
+        F: PacketFloat<N>
+    {
+        packet.rays().map(|ray| self.raytrace(&ray))
+    }
 }
 
 impl<F, const D: usize, I> Shape<F, D> for I
@@ -44,4 +98,344 @@ where
             .reduce(RaytraceWithNorm::min)
             .unwrap_or_else(RaytraceWithNorm::miss)
     }
+
+    fn raytrace_with_id(&self, ray: &Ray<F, D>) -> RaytraceWithId<F, D>
+    {
+        self.into_iter()
+            .map(|shape| shape.raytrace_with_id(ray))
+            .reduce(RaytraceWithId::min)
+            .unwrap_or_else(RaytraceWithId::miss)
+    }
+
+    fn raytrace_prepared(&self, ray: &PreparedRay<F, D>) -> Raytrace<F, D>
+    {
+        self.into_iter()
+            .map(|shape| shape.raytrace_prepared(ray))
+            .reduce(Raytrace::min)
+            .unwrap_or_else(Raytrace::miss)
+    }
+
+    fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+    {
+        self.into_iter()
+            .map(|shape| shape.raytrace_batch(rays))
+            .reduce(RaytraceBatch::min)
+            .unwrap_or_else(|| RaytraceBatch::miss(rays.len()))
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::FRAC_PI_3;
+
+    use crate::{texture::{NormalMap, Solid}, tests::Rng, Ray};
+
+    use super::{
+        nd::{HyperCube, HyperPlane, HyperRectangle, HyperSphere},
+        Shape,
+        Transform,
+        _2d::{Polygon, Segment},
+        _3d::{Annulus, Disk, Tetrahedron}
+    };
+
+    const EPS: f64 = 1e-9;
+
+    fn dot<const D: usize>(a: [f64; D], b: [f64; D]) -> f64
+    {
+        a.into_iter().zip(b).map(|(a, b)| a*b).sum()
+    }
+
+    fn sub<const D: usize>(a: [f64; D], b: [f64; D]) -> [f64; D]
+    {
+        core::array::from_fn(|i| a[i] - b[i])
+    }
+
+    fn on_plane<const D: usize>(x: [f64; D], r: [f64; D], n: [f64; D]) -> bool
+    {
+        dot(sub(x, r), n).abs() <= EPS*dot(n, n).sqrt()*(1.0 + dot(x, x).sqrt())
+    }
+
+    fn on_sphere<const D: usize>(x: [f64; D], r0: [f64; D], r: f64) -> bool
+    {
+        let d = sub(x, r0);
+        (dot(d, d).sqrt() - r).abs() <= EPS*(1.0 + r)
+    }
+
+    fn on_box<const D: usize>(x: [f64; D], c1: [f64; D], c2: [f64; D]) -> bool
+    {
+        (0..D).all(|k| x[k] >= c1[k] - EPS && x[k] <= c2[k] + EPS)
+            && (0..D).any(|k| (x[k] - c1[k]).abs() <= EPS || (x[k] - c2[k]).abs() <= EPS)
+    }
+
+    /// Traces random rays with directions of random length, checking that every hit lands on the surface and that `t` scales inversely with the direction.
+    fn check<S, const D: usize>(shape: &S, on_surface: impl Fn([f64; D]) -> bool)
+    where
+        S: Shape<f64, D>
+    {
+        let mut rng = Rng::new(0x5eed);
+        let mut hits = 0;
+        for _ in 0..2000
+        {
+            let ray = Ray {
+                r: rng.array(-4.0, 4.0),
+                v: rng.array::<D>(-1.0, 1.0).map(|v| v*rng.range(0.1, 10.0))
+            };
+
+            let raytrace = shape.raytrace(&ray);
+            let raytrace_with_norm = shape.raytrace_with_norm(&ray);
+            assert_eq!(raytrace.is_hit(), raytrace_with_norm.is_hit());
+            if !raytrace.is_hit()
+            {
+                continue
+            }
+            hits += 1;
+
+            let x = ray.propagate(raytrace.t);
+            assert!(on_surface(x), "{x:?} for {ray:?} is not on the surface");
+            assert!((raytrace.t - raytrace_with_norm.t).abs() <= EPS*(1.0 + raytrace.t));
+
+            let n = raytrace_with_norm.n.unwrap();
+            assert!((dot(n, n) - 1.0).abs() <= EPS);
+            assert!(raytrace_with_norm.uv.is_some_and(|uv| uv.iter().all(|uv| uv.is_finite())));
+
+            let tangent = raytrace_with_norm.tangent.unwrap();
+            let bitangent = raytrace_with_norm.bitangent.unwrap();
+            assert!((dot(tangent, tangent) - 1.0).abs() <= EPS && dot(tangent, n).abs() <= EPS);
+            if D > 2
+            {
+                assert!((dot(bitangent, bitangent) - 1.0).abs() <= EPS);
+                assert!(dot(bitangent, n).abs() <= EPS && dot(bitangent, tangent).abs() <= EPS);
+            }
+
+            let scaled = Ray {
+                r: ray.r,
+                v: ray.v.map(|v| v*2.0)
+            };
+            let t = shape.raytrace(&scaled).t;
+            assert!((t*2.0 - raytrace.t).abs() <= EPS*(1.0 + raytrace.t), "{t} is not half of {}", raytrace.t);
+        }
+        assert!(hits > 100, "only {hits} rays hit");
+    }
+
+    #[test]
+    fn test_hypersphere()
+    {
+        let r0 = [0.5, -0.25, 1.0];
+        check(&HyperSphere::new(r0, 2.0), |x| on_sphere(x, r0, 2.0));
+        check(&HyperSphere::new([1.0, 0.0, 0.0, -1.0], 2.5), |x| on_sphere(x, [1.0, 0.0, 0.0, -1.0], 2.5));
+    }
+
+    #[test]
+    fn test_hyperrectangle()
+    {
+        let (c1, c2) = ([-1.0, -2.0, 0.0], [2.0, 1.0, 0.5]);
+        check(&HyperRectangle {c1, c2}, |x| on_box(x, c1, c2));
+        check(&HyperCube {center: [0.0, 1.0, 0.0, 0.0], radius: 2.0}, |x| on_box(x, [-2.0, -1.0, -2.0, -2.0], [2.0, 3.0, 2.0, 2.0]));
+    }
+
+    #[test]
+    fn test_hyperplane()
+    {
+        let (r, n) = ([0.0, 1.0, -1.0], [1.0, 2.0, 0.5]);
+        check(&HyperPlane {r, n}, |x| on_plane(x, r, n));
+    }
+
+    #[test]
+    fn test_planar()
+    {
+        let (r0, n) = ([0.5, 0.0, 0.0], [0.0, 1.0, 1.0]);
+        check(&Disk::new(r0, n, 3.0), |x| on_plane(x, r0, n) && dot(sub(x, r0), sub(x, r0)) <= 9.0 + EPS);
+        check(&Annulus::new(r0, n, 1.0, 3.0), |x| {
+            let d2 = dot(sub(x, r0), sub(x, r0));
+            on_plane(x, r0, n) && (1.0 - EPS..=9.0 + EPS).contains(&d2)
+        });
+    }
+
+    #[test]
+    fn test_tetrahedron()
+    {
+        let shape = Tetrahedron {
+            center: [0.5, 1.0, -0.5],
+            diameters: [6.0, 5.0, 6.0, 7.0]
+        };
+        let v = shape.vertices();
+        check(&shape, |x| {
+            (0..4).any(|i| {
+                let [a, b, c] = [v[i], v[(i + 1) % 4], v[(i + 2) % 4]];
+                let [ab, ac, ax] = [sub(b, a), sub(c, a), sub(x, a)];
+                let n = [ab[1]*ac[2] - ab[2]*ac[1], ab[2]*ac[0] - ab[0]*ac[2], ab[0]*ac[1] - ab[1]*ac[0]];
+                let side = |p: [f64; 3]| dot(sub(p, a), n);
+                dot(ax, n).abs() <= EPS*dot(n, n).sqrt()*4.0 && side(x)*side(v[(i + 3) % 4]) >= -EPS
+            })
+        });
+    }
+
+    #[test]
+    fn test_segment()
+    {
+        let (r1, r2) = ([-1.0, 0.5], [2.0, -1.0]);
+        let on_segment = |x: [f64; 2]| {
+            let d = sub(r2, r1);
+            let s = dot(sub(x, r1), d)/dot(d, d);
+            on_plane(x, r1, [-d[1], d[0]]) && (-EPS..=1.0 + EPS).contains(&s)
+        };
+        check(&Segment::new(r1, r2), on_segment);
+
+        let square = Polygon::new([[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]]);
+        check(&square, |x| on_box(x, [-1.0, -1.0], [1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_transform()
+    {
+        let r0 = [0.5, -0.25, 1.0];
+        let shape = Transform::new(HyperSphere::new(r0, 1.5))
+            .scale([2.0, 1.0, 0.5])
+            .rotate([1.0, 1.0, 0.0], FRAC_PI_3)
+            .translate([0.0, 1.0, -1.0]);
+        check(&shape, |x| on_sphere(shape.inv_transform_pos(x), r0, 1.5));
+
+        let (c1, c2) = ([-1.0, -1.0, -1.0], [1.0, 2.0, 1.0]);
+        let shape = Transform::new(HyperRectangle {c1, c2})
+            .rotate_axes(0, 2, FRAC_PI_3)
+            .scale([1.0, 3.0, 0.5]);
+        check(&shape, |x| on_box(shape.inv_transform_pos(x), c1, c2));
+    }
+
+    /// Asserts that `ray` hits `shape` at exactly `t`, with exactly the normal `n`.
+    fn hit<S, const D: usize>(shape: &S, r: [f64; D], v: [f64; D], t: f64, n: [f64; D])
+    where
+        S: Shape<f64, D>
+    {
+        let ray = Ray {r, v};
+        let raytrace = shape.raytrace_with_norm(&ray);
+        assert_eq!(raytrace.t, t, "{ray:?}");
+        assert_eq!(shape.raytrace(&ray).t, t, "{ray:?}");
+        assert_eq!(raytrace.n, Some(n), "{ray:?}");
+    }
+
+    fn miss<S, const D: usize>(shape: &S, r: [f64; D], v: [f64; D])
+    where
+        S: Shape<f64, D>
+    {
+        let ray = Ray {r, v};
+        assert!(!shape.raytrace(&ray).is_hit(), "{ray:?}");
+        assert!(!shape.raytrace_with_norm(&ray).is_hit(), "{ray:?}");
+    }
+
+    /// Flat faces orient their normals along the ray, while spheres point theirs outwards.
+    #[test]
+    fn test_exact()
+    {
+        let sphere = HyperSphere::new([0.0; 3], 1.0);
+        hit(&sphere, [0.0, 0.0, -3.0], [0.0, 0.0, 1.0], 2.0, [0.0, 0.0, -1.0]);
+        hit(&sphere, [0.0, 0.0, -3.0], [0.0, 0.0, 2.0], 1.0, [0.0, 0.0, -1.0]);
+        hit(&sphere, [0.0; 3], [1.0, 0.0, 0.0], 1.0, [1.0, 0.0, 0.0]);
+        miss(&sphere, [0.0, 0.0, 3.0], [0.0, 0.0, 1.0]);
+        miss(&sphere, [1.5, 0.0, -3.0], [0.0, 0.0, 1.0]);
+        hit(&HyperSphere::new([1.0, 0.0, 0.0, -1.0], 2.0), [1.0, 0.0, 0.0, -5.0], [0.0, 0.0, 0.0, 1.0], 2.0, [0.0, 0.0, 0.0, -1.0]);
+
+        let rectangle = HyperRectangle {c1: [-1.0, -2.0, 0.0], c2: [2.0, 1.0, 0.5]};
+        hit(&rectangle, [0.0, 0.0, -1.0], [0.0, 0.0, 1.0], 1.0, [0.0, 0.0, 1.0]);
+        hit(&rectangle, [5.0, 0.0, 0.25], [-1.0, 0.0, 0.0], 3.0, [-1.0, 0.0, 0.0]);
+        miss(&rectangle, [5.0, 3.0, 0.25], [-1.0, 0.0, 0.0]);
+        hit(&HyperCube {center: [0.0, 1.0, 0.0, 0.0], radius: 2.0}, [0.0, 1.0, 0.0, -4.0], [0.0, 0.0, 0.0, 0.5], 4.0, [0.0, 0.0, 0.0, 1.0]);
+
+        let plane = HyperPlane {r: [0.0, 1.0, 0.0], n: [0.0, 2.0, 0.0]};
+        hit(&plane, [3.0, 5.0, 1.0], [0.0, -2.0, 0.0], 2.0, [0.0, -1.0, 0.0]);
+        hit(&plane, [3.0, -1.0, 1.0], [0.0, 1.0, 0.0], 2.0, [0.0, 1.0, 0.0]);
+        miss(&plane, [3.0, 5.0, 1.0], [1.0, 0.0, 0.0]);
+
+        let disk = Disk::new([0.0; 3], [0.0, 0.0, 1.0], 1.0);
+        hit(&disk, [0.5, 0.0, -2.0], [0.0, 0.0, 1.0], 2.0, [0.0, 0.0, 1.0]);
+        miss(&disk, [1.5, 0.0, -2.0], [0.0, 0.0, 1.0]);
+
+        let annulus = Annulus::new([0.0; 3], [0.0, 0.0, 1.0], 0.5, 1.0);
+        hit(&annulus, [0.0, 0.75, 2.0], [0.0, 0.0, -1.0], 2.0, [0.0, 0.0, -1.0]);
+        miss(&annulus, [0.0, 0.25, 2.0], [0.0, 0.0, -1.0]);
+
+        let square = super::_3d::Polygon::new([[-1.0, -1.0, 0.0], [1.0, -1.0, 0.0], [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0]]);
+        hit(&square, [0.5, 0.5, 2.0], [0.0, 0.0, -1.0], 2.0, [0.0, 0.0, -1.0]);
+        miss(&square, [1.5, 0.5, 2.0], [0.0, 0.0, -1.0]);
+
+        let tetrahedron = Tetrahedron {center: [0.0; 3], diameters: [2.0; 4]};
+        hit(&tetrahedron, [0.0, -3.0, 0.0], [0.0, 1.0, 0.0], 3.0 - 1.0/3.0, [0.0, 1.0, 0.0]);
+        hit(&tetrahedron, [0.0; 3], [0.0, -1.0, 0.0], 1.0/3.0, [0.0, -1.0, 0.0]);
+
+        let segment = Segment::new([-1.0, 0.0], [1.0, 0.0]);
+        hit(&segment, [0.0, -2.0], [0.0, 1.0], 2.0, [0.0, 1.0]);
+        miss(&segment, [2.0, -2.0], [0.0, 1.0]);
+
+        let square = Polygon::new([[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]]);
+        hit(&square, [0.5, -3.0], [0.0, 1.0], 2.0, [0.0, 1.0]);
+        miss(&square, [1.5, -3.0], [0.0, 1.0]);
+
+        let transform = Transform::new(sphere).scale([2.0; 3]).translate([0.0, 0.0, 1.0]);
+        hit(&transform, [0.0, 0.0, -5.0], [0.0, 0.0, 1.0], 4.0, [0.0, 0.0, -1.0]);
+        hit(&transform, [0.0, 0.0, 1.0], [0.0, 1.0, 0.0], 2.0, [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_uv()
+    {
+        let ray = |r| Ray {
+            r,
+            v: [0.0, 0.0, 1.0]
+        };
+        let uv = |shape: &dyn Shape<f64, 3>, r| shape.raytrace_with_norm(&ray(r)).uv.unwrap();
+        let assert_uv = |uv: [f64; 2], uv_exp: [f64; 2]| assert!(uv.into_iter().zip(uv_exp).all(|(uv, uv_exp)| (uv - uv_exp).abs() < EPS), "{uv:?} != {uv_exp:?}");
+
+        let sphere = HyperSphere::new([0.0, 0.0, 0.0], 1.0);
+        assert_uv(uv(&sphere, [0.0, 0.0, -5.0]), [0.25, 0.5]);
+        assert_uv(uv(&Transform::new(sphere).translate([10.0, 0.0, 0.0]), [10.0, 0.0, -5.0]), [0.25, 0.5]);
+
+        let cube = HyperCube {center: [0.0, 0.0, 0.0], radius: 1.0};
+        assert_uv(uv(&cube, [0.5, -0.5, -5.0]), [0.75, 0.25]);
+        assert_uv(uv(&Transform::new(cube).scale([2.0, 2.0, 2.0]), [1.0, -1.0, -5.0]), [0.75, 0.25]);
+
+        let disk = Disk::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], 2.0);
+        let [u, v] = uv(&disk, [1.0, 0.0, -5.0]);
+        assert!(((u - 0.5).powi(2) + (v - 0.5).powi(2) - 0.0625).abs() < EPS);
+
+        let polygon = super::_3d::Polygon::new([[-1.0, -2.0, 0.0], [3.0, -2.0, 0.0], [3.0, 2.0, 0.0], [0.0, 0.0, 0.0]]);
+        assert_uv(uv(&polygon, [1.0, 0.0, -5.0]), [0.5, 0.5]);
+        for r in [[2.9, -1.9, -5.0], [2.9, 1.9, -5.0], [-0.9, -1.9, -5.0]]
+        {
+            let [u, v] = uv(&polygon, r);
+            assert!([u, v].into_iter().all(|uv| uv.min(1.0 - uv) < 0.05 && uv.min(1.0 - uv) > 0.0), "{r:?} is not near a corner of the unit square");
+        }
+
+        let tetrahedron = Tetrahedron {
+            center: [0.0, 0.0, 0.0],
+            diameters: [2.0, 2.0, 2.0, 2.0]
+        };
+        let mut rng = Rng::new(1);
+        for _ in 0..100
+        {
+            let [x, y] = rng.array(-0.5, 0.5);
+            if let Some([u, v]) = tetrahedron.raytrace_with_norm(&ray([x, y, -5.0])).uv
+            {
+                assert!(u >= -EPS && v >= -EPS && u + v <= 1.0 + EPS);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normal_map()
+    {
+        let shape = Transform::new(HyperSphere::<f64, 3>::new([0.0, 0.0, 0.0], 1.0))
+            .scale([1.0, 2.0, 1.0]);
+        let raytrace = shape.raytrace_with_norm(&Ray {
+            r: [0.0, 0.5, -5.0],
+            v: [0.0, 0.0, 1.0]
+        });
+
+        let flat = raytrace.with_normal_map(&NormalMap(Solid([0.5, 0.5, 1.0])));
+        assert_eq!(flat.n, raytrace.n);
+
+        let tilted = raytrace.with_normal_map(&Solid([1.0, 0.0, 0.0]));
+        assert!(tilted.n.unwrap().into_iter().zip(raytrace.tangent.unwrap()).all(|(n, t)| (n - t).abs() <= EPS));
+        assert_eq!(tilted.t, raytrace.t);
+    }
 }
\ No newline at end of file
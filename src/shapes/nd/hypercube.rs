@@ -2,7 +2,7 @@ use core::ops::AddAssign;
 
 use num::Float;
 
-use crate::{shapes::Shape, Ray, Raytrace, RaytraceWithNorm};
+use crate::{shapes::Shape, PacketFloat, PreparedRay, Ray, RayBatch, RayPacket, Raytrace, RaytraceBatch, RaytraceWithNorm};
 
 use super::HyperRectangle;
 
@@ -39,8 +39,25 @@ where
         HyperRectangle::from(*self).raytrace(ray)
     }
 
+    fn raytrace_prepared(&self, ray: &PreparedRay<F, D>) -> Raytrace<F, D>
+    {
+        HyperRectangle::from(*self).raytrace_prepared(ray)
+    }
+
+    fn raytrace_packet<const N: usize>(&self, packet: &RayPacket<F, D, N>) -> [Raytrace<F, D>; N]
+    where
+        F: PacketFloat<N>
+    {
+        HyperRectangle::from(*self).raytrace_packet(packet)
+    }
+
     fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>
     {
         HyperRectangle::from(*self).raytrace_with_norm(ray)
     }
+
+    fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+    {
+        HyperRectangle::from(*self).raytrace_batch(rays)
+    }
 }
\ No newline at end of file
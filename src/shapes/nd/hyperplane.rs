@@ -2,7 +2,7 @@ use core::{iter::Sum, ops::{AddAssign, DivAssign, MulAssign}};
 
 use num::Float;
 
-use crate::{shapes::Shape, vec3, Ray, Raytrace, RaytraceWithNorm};
+use crate::{packet, shapes::Shape, vec3, PacketFloat, Ray, RayBatch, RayPacket, Raytrace, RaytraceBatch, RaytraceWithNorm};
 
 #[derive(Debug, Clone, Copy)]
 pub struct HyperPlane<F, const D: usize>
@@ -35,7 +35,7 @@ impl<F, const D: usize> HyperPlane<F, D>
 where
     F: Float
 {
-    fn _raytrace(&self, ray: &Ray<F, D>) -> Option<(Raytrace<F, D>, F)>
+    pub(crate) fn _raytrace(&self, ray: &Ray<F, D>) -> Option<(Raytrace<F, D>, F)>
     where
         F: Sum
     {
@@ -48,7 +48,7 @@ where
             .zip(self.n)
             .map(|((r0, r), n)| (r0 - r)*n)
             .sum::<F>()/vn;
-        if t.is_sign_positive()
+        if t >= F::zero() && t.is_finite()
         {
             return Some((
                 Raytrace {
@@ -59,6 +59,72 @@ where
         }
         None
     }
+
+    /// Two orthonormal directions within the plane, built from the coordinate axes least aligned with the normal. In 2D the second one is zero.
+    pub fn basis(&self) -> [[F; D]; 2]
+    where
+        F: Sum
+    {
+        let dot = |a: [F; D], b: [F; D]| a.into_iter()
+            .zip(b)
+            .map(|(a, b)| a*b)
+            .sum::<F>();
+
+        let n_norm = dot(self.n, self.n).sqrt();
+        let n = self.n.map(|n| n/n_norm);
+
+        let mut axes: [usize; D] = core::array::from_fn(|k| k);
+        axes.sort_by(|&a, &b| n[a].abs().partial_cmp(&n[b].abs()).unwrap_or(core::cmp::Ordering::Equal));
+
+        let mut basis = [[F::zero(); D]; 2];
+        for i in 0..D.saturating_sub(1).min(2)
+        {
+            let mut e = [F::zero(); D];
+            e[axes[i]] = F::one();
+            for u in [n].into_iter()
+                .chain(basis[..i].iter().copied())
+            {
+                let eu = dot(e, u);
+                for (e, u) in e.iter_mut()
+                    .zip(u)
+                {
+                    *e = *e - eu*u
+                }
+            }
+            let e_norm = dot(e, e).sqrt();
+            basis[i] = e.map(|e| e/e_norm);
+        }
+        basis
+    }
+
+    pub fn uv(&self, x: [F; D]) -> [F; 2]
+    where
+        F: Sum
+    {
+        self.basis()
+            .map(|e| x.into_iter()
+                .zip(self.r)
+                .zip(e)
+                .map(|((x, r), e)| (x - r)*e)
+                .sum()
+            )
+    }
+
+    pub(crate) fn _norm(&self, vn: F) -> [F; D]
+    where
+        F: DivAssign + Sum
+    {
+        let mut n = self.n;
+        let n_norm = n.into_iter()
+            .map(|n| n*n)
+            .sum::<F>()
+            .sqrt()*vn.signum();
+        for n in n.iter_mut()
+        {
+            *n /= n_norm
+        }
+        n
+    }
 }
 
 impl<F, const D: usize> Shape<F, D> for HyperPlane<F, D>
@@ -72,21 +138,47 @@ where
             .unwrap_or_else(Raytrace::miss)
     }
 
+    fn raytrace_packet<const N: usize>(&self, packet: &RayPacket<F, D, N>) -> [Raytrace<F, D>; N]
+    where
+        F: PacketFloat<N>
+    {
+        let zero = packet::sum_zero::<F, N>();
+        let vn = packet.v.iter()
+            .zip(self.n)
+            .fold(zero, |vn, (&v, n)| vn + v*F::splat(n));
+        let t = self.r.into_iter()
+            .zip(&packet.r)
+            .zip(self.n)
+            .fold(zero, |t, ((r0, &r), n)| t + (F::splat(r0) - r)*F::splat(n))/vn;
+
+        let hit = F::simd_ge(t, F::splat(F::zero())) & F::simd_is_finite(t);
+        F::to_array(F::simd_select(hit, t, F::splat(F::infinity()))).map(|t| Raytrace {
+            t
+        })
+    }
+
     fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>
     {
         self._raytrace(ray)
-            .map(|(ray, vn)| ray.with_norm(|| {
-                    let mut n = self.n;
-                    let n_norm = n.into_iter()
-                        .map(|n| n*n)
-                        .sum::<F>()
-                        .sqrt()*vn.signum();
-                    for n in n.iter_mut()
-                    {
-                        *n /= n_norm
-                    }
-                    n
-                })
+            .map(|(raytrace, vn)| raytrace.with_norm(|| self._norm(vn))
+                .with_uv(|| self.uv(ray.propagate(raytrace.t)))
+                .with_tangents(|| self.basis())
             ).unwrap_or_else(RaytraceWithNorm::miss)
     }
+
+    fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+    {
+        // The normal only depends on which side the ray comes from
+        let n = [self._norm(F::one()), self._norm(-F::one())];
+
+        let mut batch = RaytraceBatch::miss(rays.len());
+        for (i, ray) in rays.rays().enumerate()
+        {
+            if let Some((raytrace, vn)) = self._raytrace(&ray)
+            {
+                batch.set(i, &raytrace.with_norm(|| n[(vn < F::zero()) as usize]));
+            }
+        }
+        batch
+    }
 }
\ No newline at end of file
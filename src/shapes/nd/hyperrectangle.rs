@@ -1,6 +1,6 @@
 use num::Float;
 
-use crate::{shapes::Shape, Ray, Raytrace, RaytraceWithNorm};
+use crate::{shapes::Shape, PacketFloat, PreparedRay, Ray, RayBatch, RayPacket, Raytrace, RaytraceBatch, RaytraceWithNorm};
 
 #[derive(Debug, Clone)]
 pub struct HyperRectangle<F, const D: usize>
@@ -15,6 +15,50 @@ impl<F, const D: usize> HyperRectangle<F, D>
 where
     F: Float
 {
+    /// Maps a point on the face perpendicular to axis `k` to the unit square, spanned by the two axes following `k`.
+    pub fn uv(&self, k: usize, x: [F; D]) -> [F; 2]
+    {
+        let uv = |i: usize| (x[i] - self.c1[i])/(self.c2[i] - self.c1[i]);
+        [
+            if D > 1 {uv((k + 1) % D)} else {F::zero()},
+            if D > 2 {uv((k + 2) % D)} else {F::zero()}
+        ]
+    }
+
+    /// Unit vectors along the two axes following `k`, matching the directions of [`uv`](Self::uv).
+    pub fn tangents(&self, k: usize) -> [[F; D]; 2]
+    {
+        let mut tangents = [[F::zero(); D]; 2];
+        for (i, tangent) in tangents.iter_mut()
+            .enumerate()
+            .take(D.saturating_sub(1))
+        {
+            let j = (k + 1 + i) % D;
+            tangent[j] = if self.c2[j] >= self.c1[j] {F::one()} else {-F::one()};
+        }
+        tangents
+    }
+
+    /// The range of `t` for which the ray is inside the box, found with the slab method, or `None` if the ray misses it or the box is behind it.
+    pub fn t_range(&self, ray: &PreparedRay<F, D>) -> Option<[F; 2]>
+    {
+        let r = ray.ray().r;
+        let v_inv = ray.v_inv();
+        let sign = ray.sign();
+
+        let mut t_near = F::neg_infinity();
+        let mut t_far = F::infinity();
+        for k in 0..D
+        {
+            let (c_near, c_far) = if sign[k] {(self.c2[k], self.c1[k])} else {(self.c1[k], self.c2[k])};
+            // A NaN from an origin on a plane parallel to the ray is ignored by `max` and `min`
+            t_near = t_near.max((c_near - r[k])*v_inv[k]);
+            t_far = t_far.min((c_far - r[k])*v_inv[k]);
+        }
+
+        (t_near <= t_far && t_far >= F::zero()).then_some([t_near, t_far])
+    }
+
     fn _raytrace<U>(&self, ray: &Ray<F, D>, n_init: U, n: impl Fn(usize, bool) -> U) -> (Raytrace<F, D>, U)
     {
         let eps = F::epsilon();
@@ -74,17 +118,153 @@ where
         raytrace
     }
 
+    fn raytrace_prepared(&self, ray: &PreparedRay<F, D>) -> Raytrace<F, D>
+    {
+        match self.t_range(ray)
+        {
+            Some([t_near, t_far]) => Raytrace {
+                t: if t_near >= F::zero() {t_near} else {t_far}
+            },
+            None => Raytrace::miss()
+        }
+    }
+
+    fn raytrace_packet<const N: usize>(&self, packet: &RayPacket<F, D, N>) -> [Raytrace<F, D>; N]
+    where
+        F: PacketFloat<N>
+    {
+        let eps = F::epsilon();
+        let zero = F::splat(F::zero());
+
+        let mut t_min = F::splat(F::infinity());
+        for k in 0..D
+        {
+            let nonzero = F::simd_ne(packet.v[k], zero);
+            if !F::simd_any(nonzero)
+            {
+                continue
+            }
+            for c in [self.c1[k], self.c2[k]]
+            {
+                let t = (F::splat(c) - packet.r[k])/packet.v[k];
+                let mut hit = nonzero & F::simd_ge(t, zero) & F::simd_lt(t, t_min);
+                for n in (1..D).map(|i| (k + i) % D)
+                {
+                    let x = packet.r[n] + packet.v[n]*t;
+                    hit = hit & F::simd_ge(x, F::splat(self.c1[n] - eps)) & F::simd_le(x, F::splat(self.c2[n] + eps));
+                }
+                t_min = F::simd_select(hit, t, t_min);
+            }
+        }
+
+        F::to_array(t_min).map(|t| Raytrace {
+            t
+        })
+    }
+
     fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>
     {
-        let (raytrace, n) = self._raytrace(ray, None, |k, inside| {
+        let (raytrace, hit) = self._raytrace(ray, None, |k, inside| {
             let mut n = [F::zero(); D];
             n[k] = if inside {F::one()} else {-F::one()};
-            Some(n)
+            Some((k, n))
         });
-        
-        RaytraceWithNorm {
-            raytrace,
-            n
+
+        match hit
+        {
+            Some((k, n)) => RaytraceWithNorm {
+                raytrace,
+                n: Some(n),
+                ..RaytraceWithNorm::miss()
+            }.with_uv(|| self.uv(k, ray.propagate(raytrace.t)))
+                .with_tangents(|| self.tangents(k)),
+            None => RaytraceWithNorm::miss()
+        }
+    }
+
+    fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+    {
+        let mut batch = RaytraceBatch::miss(rays.len());
+        for (i, ray) in rays.rays().enumerate()
+        {
+            let (raytrace, n) = self._raytrace(&ray, None, |k, inside| {
+                let mut n = [F::zero(); D];
+                n[k] = if inside {F::one()} else {-F::one()};
+                Some(n)
+            });
+            if n.is_some()
+            {
+                batch.set(i, &RaytraceWithNorm {
+                    raytrace,
+                    n,
+                    ..RaytraceWithNorm::miss()
+                });
+            }
+        }
+        batch
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::{shapes::{nd::HyperCube, Shape, Transform}, tests::Rng, PreparedRay, Ray};
+
+    use super::HyperRectangle;
+
+    fn check<S>(shape: &S, rng: &mut Rng) -> usize
+    where
+        S: Shape<f64, 3> + ?Sized
+    {
+        let mut hits = 0;
+        for i in 0..2000
+        {
+            let r = rng.array(-4.0, 4.0);
+            let mut v = rng.array::<3>(-1.0, 1.0);
+            if i % 7 == 0
+            {
+                v[i % 3] = 0.0;
+            }
+            let ray = Ray {
+                r,
+                v
+            };
+
+            let expected = shape.raytrace(&ray);
+            let raytrace = shape.raytrace_prepared(&PreparedRay::new(ray));
+            assert_eq!(raytrace.is_hit(), expected.is_hit(), "{ray:?}");
+            if expected.is_hit()
+            {
+                assert!((raytrace.t - expected.t).abs() <= 1e-9*(1.0 + expected.t), "{} != {} for {ray:?}", raytrace.t, expected.t);
+                hits += 1;
+            }
         }
+        hits
+    }
+
+    #[test]
+    fn test_prepared()
+    {
+        let mut rng = Rng::new(48);
+        let rectangle = HyperRectangle {c1: [-1.0, -0.5, -2.0], c2: [1.5, 0.5, 1.0]};
+        let cube = HyperCube {center: [0.25, 0.0, -0.5], radius: 1.0};
+
+        assert!(check(&rectangle, &mut rng) > 100);
+        assert!(check(&cube, &mut rng) > 100);
+        assert!(check(&[rectangle.clone(), HyperRectangle {c1: [0.0; 3], c2: [2.0, 3.0, 0.5]}], &mut rng) > 100);
+        assert!(check(&Transform::new(cube).rotate([1.0, 1.0, 0.0], 0.7).scale([1.0, 2.0, 0.5]), &mut rng) > 100);
+
+        // Inside the box the ray leaves through the far side
+        let inside = PreparedRay::new(Ray {r: [0.0; 3], v: [0.0, 0.0, -2.0]});
+        assert_eq!(rectangle.t_range(&inside), Some([-0.5, 1.0]));
+        assert_eq!(rectangle.raytrace_prepared(&inside).t, 1.0);
+
+        // Parallel to a face, starting on its plane
+        let grazing = PreparedRay::new(Ray {r: [-4.0, 0.5, 0.0], v: [1.0, 0.0, 0.0]});
+        assert_eq!(rectangle.raytrace_prepared(&grazing).t, 3.0);
+
+        let behind = PreparedRay::new(Ray {r: [0.0, 0.0, 3.0], v: [0.0, 0.0, 1.0]});
+        assert_eq!(rectangle.t_range(&behind), None);
+        assert!(!rectangle.raytrace_prepared(&behind).is_hit());
     }
 }
\ No newline at end of file
@@ -2,7 +2,7 @@ use core::{iter::Sum, ops::{AddAssign, DivAssign}};
 
 use num::Float;
 
-use crate::{shapes::Shape, Ray, Raytrace, RaytraceWithNorm};
+use crate::{packet, shapes::{nd::HyperPlane, Shape}, PacketFloat, Ray, RayBatch, RayPacket, Raytrace, RaytraceBatch, RaytraceWithNorm};
 
 #[derive(Debug, Clone, Copy)]
 pub struct HyperSphere<F, const D: usize>
@@ -24,6 +24,71 @@ where
             r
         }
     }
+
+    /// Spherical mapping, with `u` as the longitude around the second axis and `v` as the latitude from it. In 2D only `u` is used.
+    pub fn uv(&self, x: [F; D]) -> [F; 2]
+    {
+        let d: [F; D] = core::array::from_fn(|i| (x[i] - self.r0[i])/self.r);
+        let half = F::from(0.5).unwrap();
+        let pi = F::from(core::f64::consts::PI).unwrap();
+        match D
+        {
+            0 | 1 => [F::zero(); 2],
+            2 => [half + d[1].atan2(d[0])/(pi + pi), F::zero()],
+            _ => [half + d[2].atan2(d[0])/(pi + pi), d[1].max(-F::one()).min(F::one()).acos()/pi]
+        }
+    }
+
+    fn _norm(&self, ray: &Ray<F, D>, t: F) -> [F; D]
+    where
+        F: DivAssign + Sum
+    {
+        let mut n = unsafe {
+            ray.r.into_iter()
+                .zip(ray.v)
+                .zip(self.r0)
+                .map(|((r, v), r0)| r + v*t - r0)
+                .next_chunk()
+                .unwrap_unchecked()
+        };
+        let n_norm = n.into_iter()
+            .map(|n| n*n)
+            .sum::<F>()
+            .sqrt();
+        for n in n.iter_mut()
+        {
+            *n /= n_norm
+        }
+        n
+    }
+
+    /// In 3D these follow the longitude and latitude of [`uv`](Self::uv). In other dimensions, or at the poles, any orthonormal pair perpendicular to the normal
+    /// is returned.
+    pub fn tangents(&self, x: [F; D]) -> [[F; D]; 2]
+    where
+        F: Sum
+    {
+        let n: [F; D] = core::array::from_fn(|i| x[i] - self.r0[i]);
+        if D == 3
+        {
+            let n_norm = n.into_iter()
+                .map(|n| n*n)
+                .sum::<F>()
+                .sqrt();
+            let [x, y, z] = [n[0], n[1], n[2]].map(|n| n/n_norm);
+            let rho = (x*x + z*z).sqrt();
+            if rho > F::epsilon()
+            {
+                let t = [-z/rho, F::zero(), x/rho];
+                let b = [y*t[2] - z*t[1], z*t[0] - x*t[2], x*t[1] - y*t[0]];
+                return [t, b].map(|v| core::array::from_fn(|i| v[i]))
+            }
+        }
+        HyperPlane {
+            r: self.r0,
+            n
+        }.basis()
+    }
 }
 
 impl<F, const D: usize> Shape<F, D> for HyperSphere<F, D>
@@ -32,34 +97,35 @@ where
 {
     fn raytrace(&self, ray: &Ray<F, D>) -> Raytrace<F, D>
     {
-        let r2 = self.r*self.r;
-        let v_abs = ray.v.into_iter()
-            .map(|v| v*v)
-            .sum::<F>()
-            .sqrt();
         let d = unsafe {
-            self.r0.into_iter()
-            .zip(ray.r)
-            .map(|(r0, r)| r0 - r)
-            .next_chunk::<D>()
-            .unwrap_unchecked()
+            ray.r.into_iter()
+                .zip(self.r0)
+                .map(|(r, r0)| r - r0)
+                .next_chunk::<D>()
+                .unwrap_unchecked()
         };
-        let dsq = d.into_iter()
-            .map(|d| d*d)
-            .sum();
-        let a = d.into_iter()
+        let a = ray.v.into_iter()
+            .map(|v| v*v)
+            .sum::<F>();
+        let b = d.into_iter()
             .zip(ray.v)
             .map(|(d, v)| d*v)
-            .sum::<F>()/v_abs;
-        if a >= F::zero()
-        {
-            let f = r2 - dsq + a*a;
+            .sum::<F>();
+        let c = d.into_iter()
+            .map(|d| d*d)
+            .sum::<F>() - self.r*self.r;
 
-            if f >= F::zero()
+        let f = b*b - a*c;
+        if f >= F::zero()
+        {
+            let f = f.sqrt();
+            for t in [(-b - f)/a, (-b + f)/a]
             {
-                let t = (a + f.sqrt()*(r2 - dsq).signum())/v_abs;
-                return Raytrace {
-                    t
+                if t >= F::zero() && t.is_finite()
+                {
+                    return Raytrace {
+                        t
+                    }
                 }
             }
         }
@@ -67,28 +133,49 @@ where
         Raytrace::miss()
     }
 
+    fn raytrace_packet<const N: usize>(&self, packet: &RayPacket<F, D, N>) -> [Raytrace<F, D>; N]
+    where
+        F: PacketFloat<N>
+    {
+        let zero = packet::sum_zero::<F, N>();
+        let d: [F::Vector; D] = core::array::from_fn(|k| packet.r[k] - F::splat(self.r0[k]));
+        let a = packet.v.iter()
+            .fold(zero, |a, &v| a + v*v);
+        let b = d.iter()
+            .zip(&packet.v)
+            .fold(zero, |b, (&d, &v)| b + d*v);
+        let c = d.iter()
+            .fold(zero, |c, &d| c + d*d) - F::splat(self.r*self.r);
+
+        let f = b*b - a*c;
+        let real = F::simd_ge(f, F::splat(F::zero()));
+        let f = F::simd_sqrt(f);
+        let valid = |t: F::Vector| real & F::simd_ge(t, F::splat(F::zero())) & F::simd_is_finite(t);
+
+        let [t1, t2] = [(-b - f)/a, (-b + f)/a];
+        let t = F::simd_select(valid(t1), t1, F::simd_select(valid(t2), t2, F::splat(F::infinity())));
+        F::to_array(t).map(|t| Raytrace {
+            t
+        })
+    }
+
     fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>
     {
         let raytrace @ Raytrace {t} = self.raytrace(ray);
 
-        raytrace.with_norm(|| {
-            let mut n = unsafe {
-                ray.r.into_iter()
-                    .zip(ray.v)
-                    .zip(self.r0)
-                    .map(|((r, v), r0)| r + v*t - r0)
-                    .next_chunk()
-                    .unwrap_unchecked()
-            };
-            let n_norm = n.into_iter()
-                .map(|n| n*n)
-                .sum::<F>()
-                .sqrt();
-            for n in n.iter_mut()
-            {
-                *n /= n_norm
-            }
-            n
-        })
+        raytrace.with_norm(|| self._norm(ray, t))
+            .with_uv(|| self.uv(ray.propagate(t)))
+            .with_tangents(|| self.tangents(ray.propagate(t)))
+    }
+
+    fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+    {
+        let mut batch = RaytraceBatch::miss(rays.len());
+        for (i, ray) in rays.rays().enumerate()
+        {
+            let raytrace @ Raytrace {t} = self.raytrace(&ray);
+            batch.set(i, &raytrace.with_norm(|| self._norm(&ray, t)));
+        }
+        batch
     }
 }
\ No newline at end of file
@@ -0,0 +1,178 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use num::Float;
+
+use crate::{ObjectId, PreparedRay, Ray, RayBatch, Raytrace, RaytraceBatch, RaytraceWithId, RaytraceWithNorm};
+
+use super::Shape;
+
+#[derive(Debug)]
+pub struct Object<T, S>
+where
+    S: ?Sized
+{
+    pub shape: Box<S>,
+    pub data: T
+}
+
+/// A collection of differently typed shapes, each given an [`ObjectId`] that stays the same for as long as the object is in the scene.
+pub struct Scene<F, const D: usize, T = (), S = dyn Shape<F, D>>
+where
+    F: Float,
+    S: Shape<F, D> + ?Sized
+{
+    objects: Vec<Option<Object<T, S>>>,
+    len: usize,
+    marker: core::marker::PhantomData<F>
+}
+
+impl<F, const D: usize, T, S> Default for Scene<F, D, T, S>
+where
+    F: Float,
+    S: Shape<F, D> + ?Sized
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl<F, const D: usize, T, S> Scene<F, D, T, S>
+where
+    F: Float,
+    S: Shape<F, D> + ?Sized
+{
+    pub fn new() -> Self
+    {
+        Self {
+            objects: Vec::new(),
+            len: 0,
+            marker: core::marker::PhantomData
+        }
+    }
+
+    pub fn insert(&mut self, shape: Box<S>, data: T) -> ObjectId
+    {
+        let id = ObjectId(self.objects.len());
+        self.objects.push(Some(Object {
+            shape,
+            data
+        }));
+        self.len += 1;
+        id
+    }
+
+    pub fn remove(&mut self, id: ObjectId) -> Option<Object<T, S>>
+    {
+        let object = self.objects.get_mut(id.0)?.take()?;
+        self.len -= 1;
+        Some(object)
+    }
+
+    pub fn get(&self, id: ObjectId) -> Option<&Object<T, S>>
+    {
+        self.objects.get(id.0)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: ObjectId) -> Option<&mut Object<T, S>>
+    {
+        self.objects.get_mut(id.0)?.as_mut()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectId, &Object<T, S>)>
+    {
+        self.objects.iter()
+            .enumerate()
+            .filter_map(|(i, object)| object.as_ref().map(|object| (ObjectId(i), object)))
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+}
+
+impl<F, const D: usize, T, S> Shape<F, D> for Scene<F, D, T, S>
+where
+    F: Float,
+    S: Shape<F, D> + ?Sized
+{
+    fn raytrace(&self, ray: &Ray<F, D>) -> Raytrace<F, D>
+    {
+        self.iter()
+            .map(|(_, object)| object.shape.raytrace(ray))
+            .reduce(Raytrace::min)
+            .unwrap_or_else(Raytrace::miss)
+    }
+
+    fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>
+    {
+        self.raytrace_with_id(ray)
+            .without_id()
+    }
+
+    fn raytrace_with_id(&self, ray: &Ray<F, D>) -> RaytraceWithId<F, D>
+    {
+        self.iter()
+            .map(|(id, object)| object.shape.raytrace_with_norm(ray).with_id(id))
+            .reduce(RaytraceWithId::min)
+            .unwrap_or_else(RaytraceWithId::miss)
+    }
+
+    fn raytrace_prepared(&self, ray: &PreparedRay<F, D>) -> Raytrace<F, D>
+    {
+        self.iter()
+            .map(|(_, object)| object.shape.raytrace_prepared(ray))
+            .reduce(Raytrace::min)
+            .unwrap_or_else(Raytrace::miss)
+    }
+
+    fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+    {
+        self.iter()
+            .map(|(_, object)| object.shape.raytrace_batch(rays))
+            .reduce(RaytraceBatch::min)
+            .unwrap_or_else(|| RaytraceBatch::miss(rays.len()))
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::{shapes::{Shape, Transform, _3d::{Cube, Sphere}}, Ray};
+
+    use super::Scene;
+
+    #[test]
+    fn test()
+    {
+        let mut scene = Scene::<f64, 3, &str>::new();
+        let sphere = scene.insert(Box::new(Sphere::new([0.0, 0.0, 0.0], 1.0)), "sphere");
+        let cube = scene.insert(Box::new(Transform::new(Cube {center: [3.0, 0.0, 0.0], radius: 1.0})), "cube");
+
+        let ray = |x| Ray {
+            r: [x, 0.0, -5.0],
+            v: [0.0, 0.0, 1.0]
+        };
+
+        let hit = scene.raytrace_with_id(&ray(0.0));
+        assert_eq!(hit.id, Some(sphere));
+        assert_eq!(scene.get(hit.id.unwrap()).unwrap().data, "sphere");
+        assert_eq!(scene.raytrace_with_id(&ray(3.0)).id, Some(cube));
+        assert!(scene.raytrace_with_id(&ray(1.5)).is_miss());
+
+        scene.remove(sphere);
+        assert!(scene.raytrace(&ray(0.0)).is_miss());
+        assert_eq!(scene.raytrace_with_id(&ray(3.0)).id, Some(cube));
+        assert_eq!(scene.len(), 1);
+        assert!(scene.remove(sphere).is_none());
+        assert_eq!(scene.len(), 1);
+        scene.remove(cube);
+        assert!(scene.is_empty());
+    }
+}
\ No newline at end of file
@@ -2,9 +2,34 @@ use core::iter::Sum;
 
 use num::Float;
 
-use crate::{matrix, vec3, Ray, Raytrace, RaytraceWithNorm};
+use crate::{matrix::{self, SingularMatrixError}, vec2, vec3, EulerOrder, PreparedRay, Quaternion, Ray, RayBatch, Raytrace, RaytraceBatch, RaytraceWithId, RaytraceWithNorm};
 
-use super::Shape;
+use super::{Shape, _2d::Line};
+
+fn dot<F, const D: usize>(a: [F; D], b: [F; D]) -> F
+where
+    F: Float + Sum
+{
+    a.into_iter()
+        .zip(b)
+        .map(|(a, b)| a*b)
+        .sum()
+}
+
+fn normalize<F, const D: usize>(v: [F; D]) -> [F; D]
+where
+    F: Float + Sum
+{
+    let v_norm = dot(v, v).sqrt();
+    if v_norm > F::zero()
+    {
+        v.map(|v| v/v_norm)
+    }
+    else
+    {
+        v
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct Transform<F, S, const D: usize>
@@ -14,7 +39,8 @@ where
 {
     s: S,
     t: [[F; D]; D],
-    t_inv: [[F; D]; D]
+    t_inv: [[F; D]; D],
+    r: [F; D]
 }
 
 impl<F, S, const D: usize> Transform<F, S, D>
@@ -27,7 +53,8 @@ where
         Self {
             s,
             t: matrix::identity(),
-            t_inv: matrix::identity()
+            t_inv: matrix::identity(),
+            r: [F::zero(); D]
         }
     }
 
@@ -35,22 +62,100 @@ where
     where
         F: Sum
     {
-        matrix::mul_matrix_collumn(self.t, r)
+        let mut r = matrix::mul_matrix_collumn(self.t, r);
+        for (r, dr) in r.iter_mut()
+            .zip(self.r)
+        {
+            *r = *r + dr
+        }
+        r
     }
 
-    pub fn inv_transform_pos(&self, r: [F; D]) -> [F; D]
+    pub fn inv_transform_pos(&self, mut r: [F; D]) -> [F; D]
     where
         F: Sum
     {
+        for (r, dr) in r.iter_mut()
+            .zip(self.r)
+        {
+            *r = *r - dr
+        }
         matrix::mul_matrix_collumn(self.t_inv, r)
     }
 
-    pub fn transform(mut self, t: [[F; D]; D], t_inv: [[F; D]; D]) -> Self
+    /// Maps a world-space ray into the local space of the shape, such that the ray parameter `t` of any hit is the same in both spaces.
+    pub fn inv_transform_ray(&self, ray: &Ray<F, D>) -> Ray<F, D>
+    where
+        F: Sum
+    {
+        Ray {
+            r: self.inv_transform_pos(ray.r),
+            v: matrix::mul_matrix_collumn(self.t_inv, ray.v)
+        }
+    }
+
+    /// Maps the normal and tangents of a hit in local space out to world space. The `t` and `uv` of the hit stay the same.
+    pub fn transform_raytrace(&self, raytrace: RaytraceWithNorm<F, D>) -> RaytraceWithNorm<F, D>
+    where
+        F: Sum
+    {
+        let raytrace = raytrace.map_norm(|n| self.transform_norm(n))
+            .map_tangents(|v| matrix::mul_matrix_collumn(self.t, v));
+
+        // Tangents stay tangent under the linear map, but may need to be made orthonormal again
+        let tangent = raytrace.tangent.map(normalize);
+        let bitangent = raytrace.bitangent.map(|b| match tangent
+        {
+            Some(t) => {
+                let bt = dot(b, t);
+                normalize(core::array::from_fn(|i| b[i] - bt*t[i]))
+            },
+            None => normalize(b)
+        });
+        RaytraceWithNorm {
+            tangent,
+            bitangent,
+            ..raytrace
+        }
+    }
+
+    pub fn transform_norm(&self, n: [F; D]) -> [F; D]
+    where
+        F: Sum
+    {
+        let n = matrix::mul_matrix_collumn(matrix::transpose(self.t_inv), n);
+        let n_norm = n.into_iter()
+            .map(|n| n*n)
+            .sum::<F>()
+            .sqrt();
+        n.map(|n| n/n_norm)
+    }
+
+    pub fn transform(self, t: [[F; D]; D]) -> Result<Self, SingularMatrixError>
+    where
+        F: Sum
+    {
+        let t_inv = matrix::inverse(t)?;
+        Ok(self._transform(t, t_inv))
+    }
+
+    fn _transform(mut self, t: [[F; D]; D], t_inv: [[F; D]; D]) -> Self
     where
         F: Sum
     {
         self.t = matrix::mul_matrix_matrix(&t, &self.t);
-        self.t_inv = matrix::mul_matrix_matrix(&t_inv, &self.t_inv);
+        self.t_inv = matrix::mul_matrix_matrix(&self.t_inv, &t_inv);
+        self.r = matrix::mul_matrix_collumn(t, self.r);
+        self
+    }
+
+    pub fn translate(mut self, r: [F; D]) -> Self
+    {
+        for (r0, dr) in self.r.iter_mut()
+            .zip(r)
+        {
+            *r0 = *r0 + dr
+        }
         self
     }
 
@@ -58,7 +163,84 @@ where
     where
         F: Sum
     {
-        self.transform(matrix::diagonal(scale), matrix::diagonal(scale.map(|scale| scale.recip())))
+        self._transform(matrix::diagonal(scale), matrix::diagonal(scale.map(|scale| scale.recip())))
+    }
+
+    pub fn rotate_axes(self, i: usize, j: usize, theta: F) -> Self
+    where
+        F: Sum
+    {
+        assert!(i < D && j < D && i != j, "rotation axes must be two distinct axes below {D}");
+
+        let c = theta.cos();
+        let s = theta.sin();
+
+        let mut t = matrix::identity();
+        t[i][i] = c;
+        t[j][j] = c;
+        t[i][j] = -s;
+        t[j][i] = s;
+
+        let mut t_inv = t;
+        t_inv[i][j] = s;
+        t_inv[j][i] = -s;
+
+        self._transform(t, t_inv)
+    }
+
+    /// Rotates by `theta` within the plane spanned by `u` and `v`, turning `u` towards `v`.
+    ///
+    /// Panics if `u` and `v` are not linearly independent, as they then span no plane.
+    pub fn rotate_plane(self, u: [F; D], v: [F; D], theta: F) -> Self
+    where
+        F: Sum
+    {
+        let dot = |a: [F; D], b: [F; D]| a.into_iter()
+            .zip(b)
+            .map(|(a, b)| a*b)
+            .sum::<F>();
+
+        let u_norm = dot(u, u).sqrt();
+        let e1 = u.map(|u| u/u_norm);
+        let uv = dot(e1, v);
+        let mut e2 = v;
+        for (e2, e1) in e2.iter_mut()
+            .zip(e1)
+        {
+            *e2 = *e2 - uv*e1
+        }
+        let v_norm = dot(e2, e2).sqrt();
+        // The part of `v` orthogonal to `u` is only rounding error when they are (nearly) parallel
+        assert!(
+            u_norm > F::zero() && u_norm.is_finite() && v_norm > dot(v, v).sqrt()*F::epsilon().sqrt(),
+            "rotation plane must be spanned by two independent vectors"
+        );
+        let e2 = e2.map(|v| v/v_norm);
+
+        let c = theta.cos() - F::one();
+        let s = theta.sin();
+
+        let mut t = matrix::identity();
+        for (i, t) in t.iter_mut()
+            .enumerate()
+        {
+            for (j, t) in t.iter_mut()
+                .enumerate()
+            {
+                *t = *t + c*(e1[i]*e1[j] + e2[i]*e2[j]) + s*(e2[i]*e1[j] - e1[i]*e2[j])
+            }
+        }
+
+        let mut t_inv = t;
+        for i in 0..D
+        {
+            for j in 0..D
+            {
+                t_inv[i][j] = t[j][i]
+            }
+        }
+
+        self._transform(t, t_inv)
     }
 }
 
@@ -74,7 +256,7 @@ where
         let c = theta.cos();
         let s = theta.sin();
 
-        self.transform(
+        self._transform(
             [
                 [c, -s],
                 [s, c],
@@ -85,6 +267,71 @@ where
             ]
         )
     }
+
+    pub fn shear_x(self, k: F) -> Self
+    where
+        F: Sum
+    {
+        let zero = F::zero();
+        let one = F::one();
+
+        self._transform(
+            [
+                [one, k],
+                [zero, one]
+            ],
+            [
+                [one, -k],
+                [zero, one]
+            ]
+        )
+    }
+
+    pub fn shear_y(self, k: F) -> Self
+    where
+        F: Sum
+    {
+        let zero = F::zero();
+        let one = F::one();
+
+        self._transform(
+            [
+                [one, zero],
+                [k, one]
+            ],
+            [
+                [one, zero],
+                [-k, one]
+            ]
+        )
+    }
+
+    pub fn mirror(self, n: [F; 2]) -> Self
+    where
+        F: Sum
+    {
+        let [a, b] = vec2::normalize(n);
+
+        let one = F::one();
+        let two = F::from(2.0).unwrap();
+
+        let t = [
+            [one - two*a*a, -two*a*b],
+            [-two*a*b,      one - two*b*b]
+        ];
+
+        self._transform(t, t)
+    }
+
+    pub fn mirror_line(self, line: Line<F>) -> Self
+    where
+        F: Sum
+    {
+        let r = line.r;
+        self.translate(r.map(|r| -r))
+            .mirror(line.n)
+            .translate(r)
+    }
 }
 
 impl<F, S> Transform<F, S, 3>
@@ -96,13 +343,13 @@ where
     where
         F: Sum
     {
-        let [x, y, z] = axis;
+        let [x, y, z] = vec3::normalize(axis);
 
         let c = theta.cos();
         let s = theta.sin();
         let cc = F::one() - c;
 
-        self.transform(
+        self._transform(
             [
                 [x*x*cc + c,   x*y*cc - z*s, x*z*cc + y*s],
                 [x*y*cc + z*s, y*y*cc + c,   y*z*cc - x*s],
@@ -116,6 +363,27 @@ where
         )
     }
 
+    pub fn rotate_quaternion(self, q: Quaternion<F>) -> Self
+    where
+        F: Sum
+    {
+        self._transform(q.to_matrix(), q.conjugate().to_matrix())
+    }
+
+    pub fn rotate_euler(self, order: EulerOrder, angles: [F; 3]) -> Self
+    where
+        F: Sum
+    {
+        self.rotate_quaternion(Quaternion::from_euler(order, angles))
+    }
+
+    pub fn rotate_euler_extrinsic(self, order: EulerOrder, angles: [F; 3]) -> Self
+    where
+        F: Sum
+    {
+        self.rotate_quaternion(Quaternion::from_euler_extrinsic(order, angles))
+    }
+
     pub fn mirror(self, n: [F; 3]) -> Self
     where
         F: Sum
@@ -131,7 +399,7 @@ where
             [-two*a*c,      -two*b*c,      one - two*c*c]
         ];
 
-        self.transform(t, t)
+        self._transform(t, t)
     }
 }
 
@@ -142,37 +410,147 @@ where
 {
     fn raytrace(&self, ray: &Ray<F, D>) -> Raytrace<F, D>
     {
-        let r_from = self.inv_transform_pos(ray.r);
-        let r_to = self.inv_transform_pos(ray.r_to());
-        let ray = Ray::new_from_to(r_from, r_to);
+        let ray = self.inv_transform_ray(ray);
         self.s.raytrace(&ray)
     }
 
     fn raytrace_with_norm(&self, ray: &Ray<F, D>) -> RaytraceWithNorm<F, D>
     {
-        let r_from = self.inv_transform_pos(ray.r);
-        let r_to = self.inv_transform_pos(ray.r_to());
-        let ray = Ray::new_from_to(r_from, r_to);
-        let raytrace @ RaytraceWithNorm {raytrace: Raytrace {t}, n: _} = self.s.raytrace_with_norm(&ray);
-        raytrace.map_norm(|n| {
-                let mut n0 = ray.propagate(t);
-                let mut n1 = unsafe {
-                    n0.into_iter()
-                        .zip(n)
-                        .map(|(n0, n)| n0 + n)
-                        .next_chunk()
-                        .unwrap_unchecked()
-                };
-                n0 = self.transform_pos(n0);
-                n1 = self.transform_pos(n1);
-    
-                unsafe {
-                    n1.into_iter()
-                        .zip(n0)
-                        .map(|(n1, n0)| n1 - n0)
-                        .next_chunk()
-                        .unwrap_unchecked()
-                }
+        let ray = self.inv_transform_ray(ray);
+        self.transform_raytrace(self.s.raytrace_with_norm(&ray))
+    }
+
+    fn raytrace_with_id(&self, ray: &Ray<F, D>) -> RaytraceWithId<F, D>
+    {
+        let ray = self.inv_transform_ray(ray);
+        let RaytraceWithId {raytrace, id} = self.s.raytrace_with_id(&ray);
+        RaytraceWithId {
+            raytrace: self.transform_raytrace(raytrace),
+            id
+        }
+    }
+
+    fn raytrace_prepared(&self, ray: &PreparedRay<F, D>) -> Raytrace<F, D>
+    {
+        let ray = PreparedRay::new(self.inv_transform_ray(ray.ray()));
+        self.s.raytrace_prepared(&ray)
+    }
+
+    fn raytrace_batch(&self, rays: &RayBatch<F, D>) -> RaytraceBatch<F, D>
+    {
+        let local: RayBatch<F, D> = rays.rays()
+            .map(|ray| self.inv_transform_ray(&ray))
+            .collect();
+        let mut batch = self.s.raytrace_batch(&local);
+        for i in 0..batch.len()
+        {
+            let raytrace = batch.get(i);
+            if let Some(n) = raytrace.n
+            {
+                batch.set(i, &RaytraceWithNorm {
+                    n: Some(self.transform_norm(n)),
+                    ..raytrace
+                });
+            }
+        }
+        batch
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use core::f64::consts::{FRAC_PI_2, FRAC_PI_3};
+
+    use crate::shapes::{_2d::{Circle, Line}, nd::HyperCube};
+
+    use super::Transform;
+
+    #[test]
+    fn test_2d()
+    {
+        let shape = Transform::new(Circle::new([0.0, 0.0], 1.0))
+            .rotate(FRAC_PI_3)
+            .shear_x(0.5)
+            .translate([1.0, 2.0])
+            .shear_y(-0.25)
+            .mirror_line(Line {
+                r: [3.0, 0.0],
+                n: [1.0, 1.0]
+            });
+
+        for r in [[0.0, 0.0], [1.0, -2.0], [-3.5, 0.25]]
+        {
+            let r_inv = shape.inv_transform_pos(shape.transform_pos(r));
+            assert!(r.into_iter().zip(r_inv).all(|(r, r_inv)| (r - r_inv).abs() < 1e-12));
+        }
+
+        let mirror = Transform::new(Circle::<f64>::new([0.0, 0.0], 1.0)).mirror_line(Line {
+            r: [0.0, 1.0],
+            n: [0.0, 1.0]
+        });
+        let [x, y] = mirror.transform_pos([2.0, 3.0]);
+        assert!((x - 2.0).abs() < 1e-12 && (y + 1.0).abs() < 1e-12);
+        let shape = Transform::new(Circle::<f64>::new([0.0, 0.0], 1.0));
+        assert!(shape.transform([[1.0, 2.0], [2.0, 4.0]]).is_err());
+        let shape = shape.transform([[2.0, 1.0], [0.0, 0.5]]).unwrap();
+        let [x, y] = shape.inv_transform_pos(shape.transform_pos([3.0, -1.0]));
+        assert!((x - 3.0).abs() < 1e-12 && (y + 1.0).abs() < 1e-12);
+        assert!(Transform::new(Circle::<f64>::new([0.0, 0.0], 1.0)).transform([[1e9, 0.0], [0.0, 1e-8]]).is_ok());
+    }
+
+    #[test]
+    fn test_nd()
+    {
+        let shape = Transform::new(HyperCube {
+                center: [0.0; 4],
+                radius: 1.0
+            })
+            .rotate_axes(0, 3, FRAC_PI_2)
+            .rotate_plane([1.0, 1.0, 0.0, 0.0], [0.0, 0.0, 2.0, 0.0], FRAC_PI_3);
+
+        for r in [[1.0, 0.0, 0.0, 0.0], [0.5, -2.0, 1.0, 3.0]]
+        {
+            let r_inv = shape.inv_transform_pos(shape.transform_pos(r));
+            assert!(r.into_iter().zip(r_inv).all(|(r, r_inv)| (r - r_inv).abs() < 1e-12));
+        }
+
+        let shape = Transform::new(HyperCube::<f64, 4> {
+                center: [0.0; 4],
+                radius: 1.0
+            })
+            .rotate_axes(1, 3, FRAC_PI_2);
+        let r = shape.transform_pos([0.0, 1.0, 0.0, 0.0]);
+        assert!(r.into_iter().zip([0.0, 0.0, 0.0, 1.0]).all(|(r, r_exp)| (r - r_exp).abs() < 1e-12));
+
+        let shape = Transform::new(HyperCube::<f64, 4> {
+                center: [0.0; 4],
+                radius: 1.0
+            })
+            .rotate_plane([0.0, 2.0, 0.0, 0.0], [0.0, 1.0, 0.0, 3.0], FRAC_PI_2);
+        let r = shape.transform_pos([0.0, 1.0, 0.0, 0.0]);
+        assert!(r.into_iter().zip([0.0, 0.0, 0.0, 1.0]).all(|(r, r_exp)| (r - r_exp).abs() < 1e-12));
+    }
+
+    #[test]
+    #[should_panic(expected = "independent")]
+    fn test_rotate_plane_parallel()
+    {
+        let _ = Transform::new(HyperCube::<f64, 4> {
+                center: [0.0; 4],
+                radius: 1.0
+            })
+            .rotate_plane([1.0, 1.0, 0.0, 0.0], [2.0, 2.0, 0.0, 0.0], FRAC_PI_2);
+    }
+
+    #[test]
+    #[should_panic(expected = "independent")]
+    fn test_rotate_plane_zero()
+    {
+        let _ = Transform::new(HyperCube::<f64, 4> {
+                center: [0.0; 4],
+                radius: 1.0
             })
+            .rotate_plane([0.0; 4], [0.0, 1.0, 0.0, 0.0], FRAC_PI_2);
     }
 }
\ No newline at end of file
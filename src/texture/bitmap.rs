@@ -0,0 +1,146 @@
+use alloc::vec::Vec;
+
+use num::Float;
+
+use super::{Lerp, Texture};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter
+{
+    Nearest,
+    Bilinear
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap
+{
+    Repeat,
+    Mirror,
+    Clamp
+}
+
+impl Wrap
+{
+    fn apply(self, i: isize, n: usize) -> usize
+    {
+        let n = n as isize;
+        (match self
+        {
+            Wrap::Repeat => i.rem_euclid(n),
+            Wrap::Mirror => {
+                let i = i.rem_euclid(2*n);
+                if i < n {i} else {2*n - 1 - i}
+            },
+            Wrap::Clamp => i.clamp(0, n - 1)
+        }) as usize
+    }
+}
+
+/// An image sampled over the unit square, with pixel row `0` at `v = 0`. It always has at least one pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitmap<T>
+{
+    width: usize,
+    height: usize,
+    pixels: Vec<T>,
+    filter: Filter,
+    wrap: Wrap
+}
+
+impl<T> Bitmap<T>
+{
+    pub fn new(width: usize, height: usize, pixels: Vec<T>, filter: Filter, wrap: Wrap) -> Self
+    {
+        assert!(width > 0 && height > 0, "bitmap must have at least one pixel");
+        assert_eq!(pixels.len(), width*height, "bitmap must have exactly width*height pixels");
+        Self {
+            width,
+            height,
+            pixels,
+            filter,
+            wrap
+        }
+    }
+
+    pub fn with_filter(self, filter: Filter) -> Self
+    {
+        Self {
+            filter,
+            ..self
+        }
+    }
+
+    pub fn with_wrap(self, wrap: Wrap) -> Self
+    {
+        Self {
+            wrap,
+            ..self
+        }
+    }
+
+    pub fn width(&self) -> usize
+    {
+        self.width
+    }
+
+    pub fn height(&self) -> usize
+    {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[T]
+    {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [T]
+    {
+        &mut self.pixels
+    }
+
+    pub fn filter(&self) -> Filter
+    {
+        self.filter
+    }
+
+    pub fn wrap(&self) -> Wrap
+    {
+        self.wrap
+    }
+
+    pub fn pixel(&self, x: isize, y: isize) -> &T
+    {
+        let x = self.wrap.apply(x, self.width);
+        let y = self.wrap.apply(y, self.height);
+        &self.pixels[y*self.width + x]
+    }
+}
+
+impl<F, T> Texture<F, T> for Bitmap<T>
+where
+    F: Float,
+    T: Lerp<F>
+{
+    fn sample(&self, uv: [F; 2]) -> T
+    {
+        let [u, v] = uv;
+        let x = u*F::from(self.width).unwrap();
+        let y = v*F::from(self.height).unwrap();
+
+        match self.filter
+        {
+            Filter::Nearest => *self.pixel(x.floor().to_isize().unwrap_or(0), y.floor().to_isize().unwrap_or(0)),
+            Filter::Bilinear => {
+                let half = F::from(0.5).unwrap();
+                let (x, y) = (x - half, y - half);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0.to_isize().unwrap_or(0), y0.to_isize().unwrap_or(0));
+
+                let top = self.pixel(x0, y0).lerp(*self.pixel(x0 + 1, y0), tx);
+                let bottom = self.pixel(x0, y0 + 1).lerp(*self.pixel(x0 + 1, y0 + 1), tx);
+                top.lerp(bottom, ty)
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,27 @@
+use num::Float;
+
+use super::Texture;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Checkerboard<F, T>
+where
+    F: Float
+{
+    pub a: T,
+    pub b: T,
+    pub scale: [F; 2]
+}
+
+impl<F, T> Texture<F, T> for Checkerboard<F, T>
+where
+    F: Float,
+    T: Copy
+{
+    fn sample(&self, uv: [F; 2]) -> T
+    {
+        let [u, v] = uv;
+        let [su, sv] = self.scale;
+        let k = (u*su).floor() + (v*sv).floor();
+        if (k*F::from(0.5).unwrap()).fract() == F::zero() {self.a} else {self.b}
+    }
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use num::Float;
+
+use super::{Lerp, Texture};
+
+/// Piecewise linear blend between color stops, along the direction `axis` in texture space.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient<F, T>
+where
+    F: Float
+{
+    pub axis: [F; 2],
+    stops: Vec<(F, T)>
+}
+
+impl<F, T> Gradient<F, T>
+where
+    F: Float
+{
+    pub fn new(axis: [F; 2], mut stops: Vec<(F, T)>) -> Self
+    {
+        stops.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        Self {
+            axis,
+            stops
+        }
+    }
+
+    pub fn stops(&self) -> &[(F, T)]
+    {
+        &self.stops
+    }
+}
+
+impl<F, T> Texture<F, T> for Gradient<F, T>
+where
+    F: Float,
+    T: Lerp<F> + Default
+{
+    fn sample(&self, uv: [F; 2]) -> T
+    {
+        let [u, v] = uv;
+        let [a, b] = self.axis;
+        let x = u*a + v*b;
+
+        let i = self.stops.partition_point(|&(s, _)| s <= x);
+        match (i.checked_sub(1).and_then(|i| self.stops.get(i)), self.stops.get(i))
+        {
+            (Some(&(s0, c0)), Some(&(s1, c1))) => c0.lerp(c1, (x - s0)/(s1 - s0)),
+            (Some(&(_, c)), None) | (None, Some(&(_, c))) => c,
+            (None, None) => T::default()
+        }
+    }
+}
\ No newline at end of file
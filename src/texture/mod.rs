@@ -0,0 +1,129 @@
+moddef::moddef!(
+    flat(pub) mod {
+        bitmap,
+        checkerboard,
+        gradient,
+        noise,
+        normal_map,
+        solid
+    }
+);
+
+use alloc::boxed::Box;
+
+use num::Float;
+
+/// Something that can be sampled at surface coordinates, such as the `uv` of a hit, to give a color or any other material parameter.
+pub trait Texture<F, T>
+where
+    F: Float
+{
+    fn sample(&self, uv: [F; 2]) -> T;
+}
+
+impl<F, T, X> Texture<F, T> for &X
+where
+    F: Float,
+    X: Texture<F, T> + ?Sized
+{
+    fn sample(&self, uv: [F; 2]) -> T
+    {
+        (**self).sample(uv)
+    }
+}
+impl<F, T, X> Texture<F, T> for Box<X>
+where
+    F: Float,
+    X: Texture<F, T> + ?Sized
+{
+    fn sample(&self, uv: [F; 2]) -> T
+    {
+        (**self).sample(uv)
+    }
+}
+
+/// Linear interpolation, used by textures that blend between values.
+pub trait Lerp<F>: Copy
+{
+    fn lerp(self, rhs: Self, t: F) -> Self;
+}
+
+impl Lerp<f32> for f32
+{
+    fn lerp(self, rhs: Self, t: f32) -> Self
+    {
+        self + (rhs - self)*t
+    }
+}
+impl Lerp<f64> for f64
+{
+    fn lerp(self, rhs: Self, t: f64) -> Self
+    {
+        self + (rhs - self)*t
+    }
+}
+impl<F, T, const N: usize> Lerp<F> for [T; N]
+where
+    F: Copy,
+    T: Lerp<F>
+{
+    fn lerp(self, rhs: Self, t: F) -> Self
+    {
+        core::array::from_fn(|i| self[i].lerp(rhs[i], t))
+    }
+}
+
+#[cfg(test)]
+mod test
+{
+    use super::{Bitmap, Checkerboard, Filter, Gradient, Noise, NoiseKind, Solid, Texture, Wrap};
+
+    #[test]
+    fn test()
+    {
+        let red = [1.0, 0.0, 0.0];
+        let blue = [0.0, 0.0, 1.0];
+
+        assert_eq!(Solid(red).sample([0.3, 0.7]), red);
+
+        let checkerboard = Checkerboard {
+            a: red,
+            b: blue,
+            scale: [2.0, 2.0]
+        };
+        assert_eq!(checkerboard.sample([0.25, 0.25]), red);
+        assert_eq!(checkerboard.sample([0.75, 0.25]), blue);
+        assert_eq!(checkerboard.sample([0.75, 0.75]), red);
+
+        let gradient = Gradient::new([1.0, 0.0], vec![(0.0, 0.0), (1.0, 2.0), (2.0, 0.0)]);
+        assert_eq!(gradient.sample([0.5, 0.9]), 1.0);
+        assert_eq!(gradient.sample([1.5, 0.0]), 1.0);
+        assert_eq!(gradient.sample([-1.0, 0.0]), 0.0);
+
+        let bitmap = Bitmap::new(2, 2, vec![0.0, 1.0, 2.0, 3.0], Filter::Nearest, Wrap::Repeat);
+        assert_eq!(bitmap.sample([0.25, 0.75]), 2.0);
+        assert_eq!(bitmap.sample([1.25, -0.25]), 2.0);
+        let bitmap = bitmap.with_filter(Filter::Bilinear).with_wrap(Wrap::Clamp);
+        assert_eq!(bitmap.sample([0.5, 0.5]), 1.5);
+        assert_eq!(bitmap.sample([-1.0, 0.0]), 0.0);
+        let bitmap = bitmap.with_wrap(Wrap::Mirror);
+        assert_eq!((bitmap.width(), bitmap.height(), bitmap.filter(), bitmap.wrap()), (2, 2, Filter::Bilinear, Wrap::Mirror));
+        assert_eq!(bitmap.sample([-0.25, 0.25]), bitmap.sample([0.25, 0.25]));
+
+        for kind in [NoiseKind::Value, NoiseKind::Perlin]
+        {
+            let noise = Noise::new(kind, 0.0, 1.0, 4.0, 3, 7);
+            let samples: Vec<f64> = (0..100).map(|i| noise.sample([i as f64*0.0731, i as f64*0.0417])).collect();
+            assert!(samples.iter().all(|&x| (0.0..=1.0).contains(&x)));
+            assert!(samples.iter().any(|&x| (x - samples[0]).abs() > 1e-3));
+            assert_eq!(noise.sample([0.3, 0.4]), noise.sample([0.3, 0.4]));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one pixel")]
+    fn test_empty_bitmap()
+    {
+        let _ = Bitmap::<f64>::new(0, 0, vec![], Filter::Nearest, Wrap::Repeat);
+    }
+}
\ No newline at end of file
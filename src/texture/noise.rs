@@ -0,0 +1,112 @@
+use num::Float;
+
+use super::{Lerp, Texture};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind
+{
+    Value,
+    Perlin
+}
+
+/// Fractal noise over texture space, blending from `low` to `high`. Each octave doubles the frequency and halves the amplitude of the previous one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Noise<F, T>
+where
+    F: Float
+{
+    pub kind: NoiseKind,
+    pub low: T,
+    pub high: T,
+    pub frequency: F,
+    pub octaves: u32,
+    pub seed: u32
+}
+
+impl<F, T> Noise<F, T>
+where
+    F: Float
+{
+    pub fn new(kind: NoiseKind, low: T, high: T, frequency: F, octaves: u32, seed: u32) -> Self
+    {
+        Self {
+            kind,
+            low,
+            high,
+            frequency,
+            octaves,
+            seed
+        }
+    }
+
+    fn hash(&self, x: i64, y: i64) -> u32
+    {
+        let mut h = self.seed ^ (x as u32).wrapping_mul(0x27d4_eb2d) ^ (y as u32).wrapping_mul(0x1656_67b1);
+        h ^= h >> 15;
+        h = h.wrapping_mul(0x85eb_ca6b);
+        h ^= h >> 13;
+        h = h.wrapping_mul(0xc2b2_ae35);
+        h ^ (h >> 16)
+    }
+
+    /// Value at a lattice point, or the gradient dotted with the offset to it, in the range `[-1, 1]`.
+    fn corner(&self, x: i64, y: i64, dx: F, dy: F) -> F
+    {
+        let h = self.hash(x, y);
+        match self.kind
+        {
+            NoiseKind::Value => F::from(h).unwrap()/F::from(u32::MAX).unwrap()*F::from(2.0).unwrap() - F::one(),
+            NoiseKind::Perlin => {
+                let theta = F::from(h).unwrap()/F::from(u32::MAX).unwrap()*F::from(core::f64::consts::TAU).unwrap();
+                // The largest possible magnitude of 2D gradient noise is sqrt(1/2)
+                (theta.cos()*dx + theta.sin()*dy)*F::from(core::f64::consts::SQRT_2).unwrap()
+            }
+        }
+    }
+
+    fn octave(&self, u: F, v: F) -> F
+    {
+        let (u0, v0) = (u.floor(), v.floor());
+        let (du, dv) = (u - u0, v - v0);
+        let (x, y) = (u0.to_i64().unwrap_or(0), v0.to_i64().unwrap_or(0));
+
+        let fade = |t: F| t*t*t*(t*(t*F::from(6.0).unwrap() - F::from(15.0).unwrap()) + F::from(10.0).unwrap());
+        let (su, sv) = (fade(du), fade(dv));
+
+        let c00 = self.corner(x, y, du, dv);
+        let c10 = self.corner(x + 1, y, du - F::one(), dv);
+        let c01 = self.corner(x, y + 1, du, dv - F::one());
+        let c11 = self.corner(x + 1, y + 1, du - F::one(), dv - F::one());
+
+        let c0 = c00 + (c10 - c00)*su;
+        let c1 = c01 + (c11 - c01)*su;
+        c0 + (c1 - c0)*sv
+    }
+}
+
+impl<F, T> Texture<F, T> for Noise<F, T>
+where
+    F: Float,
+    T: Lerp<F>
+{
+    fn sample(&self, uv: [F; 2]) -> T
+    {
+        let [u, v] = uv;
+        let half = F::from(0.5).unwrap();
+
+        let mut sum = F::zero();
+        let mut total = F::zero();
+        let mut amplitude = F::one();
+        let mut frequency = self.frequency;
+        for _ in 0..self.octaves.max(1)
+        {
+            sum = sum + self.octave(u*frequency, v*frequency)*amplitude;
+            total = total + amplitude;
+            amplitude = amplitude*half;
+            frequency = frequency + frequency;
+        }
+
+        let t = (sum/total*half + half).max(F::zero()).min(F::one());
+        self.low.lerp(self.high, t)
+    }
+}
\ No newline at end of file
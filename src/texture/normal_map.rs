@@ -0,0 +1,18 @@
+use num::Float;
+
+use super::Texture;
+
+/// Decodes a normal map stored as colors in `[0, 1]` into tangent-space normals in `[-1, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalMap<X>(pub X);
+
+impl<F, X> Texture<F, [F; 3]> for NormalMap<X>
+where
+    F: Float,
+    X: Texture<F, [F; 3]>
+{
+    fn sample(&self, uv: [F; 2]) -> [F; 3]
+    {
+        self.0.sample(uv).map(|c| c + c - F::one())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,17 @@
+use num::Float;
+
+use super::Texture;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Solid<T>(pub T);
+
+impl<F, T> Texture<F, T> for Solid<T>
+where
+    F: Float,
+    T: Copy
+{
+    fn sample(&self, _uv: [F; 2]) -> T
+    {
+        self.0
+    }
+}
\ No newline at end of file
@@ -12,6 +12,14 @@ where
 {
     magnitude_squared(v).sqrt()
 }
+pub fn add<F>(lhs: [F; 2], rhs: [F; 2]) -> [F; 2]
+where
+    F: Float
+{
+    let [x_lhs, y_lhs] = lhs;
+    let [x_rhs, y_rhs] = rhs;
+    [x_lhs + x_rhs, y_lhs + y_rhs]
+}
 pub fn sub<F>(lhs: [F; 2], rhs: [F; 2]) -> [F; 2]
 where
     F: Float
@@ -35,4 +43,19 @@ where
     let [x, y] = v;
     let m = magnitude(v);
     [x/m, y/m]
+}
+pub fn mul_cross<F>(lhs: [F; 2], rhs: [F; 2]) -> F
+where
+    F: Float
+{
+    let [x_lhs, y_lhs] = lhs;
+    let [x_rhs, y_rhs] = rhs;
+    x_lhs*y_rhs - y_lhs*x_rhs
+}
+pub fn perpendicular<F>(v: [F; 2]) -> [F; 2]
+where
+    F: Float
+{
+    let [x, y] = v;
+    [-y, x]
 }
\ No newline at end of file
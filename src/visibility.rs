@@ -0,0 +1,103 @@
+use core::{cmp::Ordering, iter::Sum, ops::{AddAssign, DivAssign}};
+
+use alloc::vec::Vec;
+use num::Float;
+
+use crate::{shapes::{Shape, _2d::Segment}, Ray, Raytrace};
+
+fn cast<F>(r: [F; 2], theta: F, range: F, raytrace: impl Fn(&Ray<F, 2>) -> Raytrace<F, 2>) -> [F; 2]
+where
+    F: Float
+{
+    let ray = Ray {
+        r,
+        v: [theta.cos(), theta.sin()]
+    };
+    ray.propagate(raytrace(&ray).t.min(range))
+}
+
+/// Casts rays from `r` towards every end-point of the walls (and slightly past each side of it), returning the visible region as a polygon sorted by angle.
+pub fn visibility_polygon<F>(r: [F; 2], segments: &[Segment<F>], range: F) -> Vec<[F; 2]>
+where
+    F: Float + AddAssign + DivAssign + Sum
+{
+    let eps = F::epsilon().sqrt();
+
+    let mut angles: Vec<F> = segments.iter()
+        .flat_map(|segment| [segment.r1, segment.r2])
+        .flat_map(|[x, y]| {
+            let theta = (y - r[1]).atan2(x - r[0]);
+            // Wrapped back into (-π, π], so that the samples next to a vertex behind `r` keep their place in the angular order
+            [theta - eps, theta, theta + eps].map(|theta| theta.sin().atan2(theta.cos()))
+        })
+        .collect();
+    angles.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    angles.into_iter()
+        .map(|theta| cast(r, theta, range, |ray| segments.iter()
+            .map(|segment| segment.raytrace(ray))
+            .reduce(Raytrace::min)
+            .unwrap_or_else(Raytrace::miss)
+        ))
+        .collect()
+}
+
+/// Casts `N` rays from `r`, spread evenly across the angle `fov` centered around the direction `theta`, and returns where each of them stopped.
+pub fn field_of_view<F, S, const N: usize>(shape: &S, r: [F; 2], theta: F, fov: F, range: F) -> [[F; 2]; N]
+where
+    F: Float,
+    S: Shape<F, 2> + ?Sized
+{
+    let half = F::from(0.5).unwrap();
+    core::array::from_fn(|i| {
+        let x = if N > 1
+        {
+            F::from(i).unwrap()/F::from(N - 1).unwrap() - half
+        }
+        else
+        {
+            F::zero()
+        };
+        cast(r, theta + fov*x, range, |ray| shape.raytrace(ray))
+    })
+}
+
+#[cfg(test)]
+mod test
+{
+    use crate::shapes::_2d::{Polygon, Segment};
+
+    #[test]
+    fn test()
+    {
+        let room = Polygon::new([
+            [-1.0, -1.0],
+            [1.0, -1.0],
+            [1.0, 1.0],
+            [-1.0, 1.0]
+        ]);
+        let walls: Vec<Segment<f64>> = room.segments().collect();
+
+        let visible = super::visibility_polygon([0.0, 0.0], &walls, 10.0);
+        for corner in room.vertices
+        {
+            assert!(visible.iter().any(|x| (x[0] - corner[0]).abs() < 1e-6 && (x[1] - corner[1]).abs() < 1e-6));
+        }
+        assert!(visible.iter().all(|x| x[0].abs() <= 1.0 + 1e-6 && x[1].abs() <= 1.0 + 1e-6));
+
+        // A wall ending on the negative x-axis, where the angles wrap around
+        let mut walls = walls;
+        walls.push(Segment::new([-0.5, 0.0], [-0.5, 0.5]));
+        let visible = super::visibility_polygon([0.0, 0.0], &walls, 10.0);
+        let angles: Vec<f64> = visible.iter()
+            .map(|[x, y]| y.atan2(*x))
+            .collect();
+        assert!(angles.windows(2).all(|w| w[0] <= w[1] + 1e-9), "{visible:?} is not sorted by angle");
+        assert!(visible.iter().any(|x| (x[0] + 0.5).abs() < 1e-6 && x[1].abs() < 1e-6));
+
+        let [left, front, right] = super::field_of_view(&room, [0.0, 0.0], 0.0, core::f64::consts::FRAC_PI_2, 10.0);
+        assert!((front[0] - 1.0).abs() < 1e-9 && front[1].abs() < 1e-9);
+        assert!((left[0] - 1.0).abs() < 1e-9 && (left[1] + 1.0).abs() < 1e-9);
+        assert!((right[0] - 1.0).abs() < 1e-9 && (right[1] - 1.0).abs() < 1e-9);
+    }
+}
\ No newline at end of file